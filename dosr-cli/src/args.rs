@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use dosr::{Band, CipherKind, ErrorCorrection, WavFormat};
 
 #[derive(Subcommand)]
 pub enum Action {
@@ -6,21 +7,191 @@ pub enum Action {
         /// message to encode
         message: String,
 
-        /// output file path
+        /// interpret the message as a hex string of bytes
+        #[clap(long)]
+        hex: bool,
+
+        /// output file path, or "-" for stdout
         output_path: String,
 
+        /// also write each frame to its own WAV file in this directory
+        #[clap(long)]
+        split_frames: Option<String>,
+
+        /// amplitude of each tone, between 0 and 1 [default: 1 / number of simultaneous tones]
+        #[clap(long, value_parser = parse_amplitude)]
+        amplitude: Option<f32>,
+
+        /// scale every frame up to full scale
+        #[clap(long)]
+        normalize: bool,
+
+        /// transmit the message this many times
+        #[clap(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+        repeat: u32,
+
+        /// frames of silence between the repetitions of the message
+        #[clap(long, default_value = "1")]
+        gap_frames: usize,
+
+        /// bits per sample of the WAV file: 32 for float, 16 for integer PCM
+        #[clap(long, default_value = "32", value_parser = parse_bits)]
+        bits: WavFormat,
+
+        /// encryption method: symmetric, asymmetric
+        #[command(subcommand)]
+        encryption_options: Option<Encryption>,
+    },
+    /// encode every line of a file, or every file of a directory, into its own WAV file
+    BatchEncode {
+        /// file with a message per line, or directory with a message per file
+        input: String,
+
+        /// directory to write the WAV files and their manifest to
+        output_dir: String,
+
+        /// interpret the messages as hex strings of bytes
+        #[clap(long)]
+        hex: bool,
+
         /// encryption method: symmetric, asymmetric
         #[command(subcommand)]
         encryption_options: Option<Encryption>,
     },
     Decode {
-        /// output file path
+        /// input file path, or "-" for stdin, which is read into memory in full before decoding
         input_path: String,
 
+        /// write per-frame diagnostics to this CSV file
+        #[clap(long)]
+        diagnostics_csv: Option<String>,
+
+        /// print the SNR and tone count of every frame to stderr, with the weakest SNR, to tell
+        /// whether the signal is too weak or noisy
+        #[clap(long)]
+        stats: bool,
+
+        /// channel to decode from multi-channel files: a channel index or "mix"
+        #[clap(long, default_value = "mix", value_parser = parse_channel)]
+        channel: Channel,
+
+        /// print the decoded bytes as a hex string
+        #[clap(long)]
+        hex: bool,
+
+        /// decode every message in the file, separated by silence, reporting failures without stopping
+        #[clap(long, conflicts_with_all = ["diagnostics_csv", "stats"])]
+        all: bool,
+
+        /// magnitude, relative to the strongest frequency of a frame, above which a peak is a
+        /// tone; lower it for weak or noisy signals
+        #[clap(long, default_value = "0.4", value_parser = parse_peak_threshold)]
+        peak_threshold: f32,
+
+        /// print a waterfall of the tone band to stderr before decoding
+        #[clap(long)]
+        waterfall: bool,
+
+        /// zero-pad the frames of the waterfall to this FFT size for a finer frequency grid,
+        /// at least the number of samples per frame
+        #[clap(long, requires = "waterfall")]
+        spectrogram_fft_size: Option<usize>,
+
         /// encryption method: symmetric, asymmetric
         #[command(subcommand, name = "encryption_options")]
         encryption_options: Option<Encryption>,
     },
+    /// decode a file and report how far the result is from the expected message
+    Diff {
+        /// message the file should decode to
+        expected: String,
+
+        /// input file path, or "-" for stdin
+        input_path: String,
+
+        /// interpret the expected message as a hex string of bytes
+        #[clap(long)]
+        hex: bool,
+
+        /// channel to decode from multi-channel files: a channel index or "mix"
+        #[clap(long, default_value = "mix", value_parser = parse_channel)]
+        channel: Channel,
+
+        /// encryption method: symmetric, asymmetric
+        #[command(subcommand)]
+        encryption_options: Option<Encryption>,
+    },
+    /// generate key files for encryption
+    Keygen {
+        /// kind of key: sym, asym
+        #[command(subcommand)]
+        kind: Keygen,
+    },
+    /// encode a message and play it on the default output device
+    #[cfg(feature = "play")]
+    Play {
+        /// message to play
+        message: String,
+
+        /// interpret the message as a hex string of bytes
+        #[clap(long)]
+        hex: bool,
+
+        /// amplitude of each tone, between 0 and 1 [default: 1 / number of simultaneous tones]
+        #[clap(long, value_parser = parse_amplitude)]
+        amplitude: Option<f32>,
+
+        /// play the message this many times
+        #[clap(long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+        repeat: u32,
+
+        /// play the message over and over until interrupted
+        #[clap(long = "loop", conflicts_with = "repeat")]
+        looped: bool,
+
+        /// frames of silence between the repetitions of the message
+        #[clap(long, default_value = "1")]
+        gap_frames: usize,
+
+        /// encryption method: symmetric, asymmetric
+        #[command(subcommand)]
+        encryption_options: Option<Encryption>,
+    },
+    /// decode the default input device live, printing every message as it ends
+    #[cfg(feature = "listen")]
+    Listen {
+        /// print the decoded bytes as hex strings
+        #[clap(long)]
+        hex: bool,
+
+        /// magnitude, relative to the strongest frequency of a frame, above which a peak is a
+        /// tone; lower it for weak or noisy signals
+        #[clap(long, default_value = "0.4", value_parser = parse_peak_threshold)]
+        peak_threshold: f32,
+
+        /// RMS below which a frame is silence and ends a message; raise it above the noise floor
+        /// of the microphone
+        #[clap(long, default_value = "0.01", value_parser = parse_silence_threshold)]
+        silence_threshold: f32,
+
+        /// print a waterfall row of the tone band to stderr for every frame heard
+        #[clap(long)]
+        waterfall: bool,
+
+        /// encryption method: symmetric, asymmetric
+        #[command(subcommand)]
+        encryption_options: Option<Encryption>,
+    },
+    /// print the bytes, chunks, frames and tone frequencies the message is encoded into, as JSON
+    #[cfg(feature = "serde")]
+    Explain {
+        /// message to explain
+        message: String,
+
+        /// interpret the message as a hex string of bytes
+        #[clap(long)]
+        hex: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -40,6 +211,164 @@ pub enum Encryption {
     },
 }
 
+#[derive(Subcommand)]
+pub enum Keygen {
+    /// random key for symmetric encryption, as long as the keys of the cipher
+    Sym {
+        /// path of the key file to create
+        key_path: String,
+    },
+    /// secp256k1 keypair for asymmetric encryption
+    Asym {
+        /// path of the private key der file to create
+        private_key_path: String,
+
+        /// path of the public key der file to create
+        public_key_path: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Channel {
+    /// decode a single channel
+    Index(u16),
+    /// average all channels down to mono
+    Mix,
+}
+
+fn parse_channel(s: &str) -> Result<Channel, String> {
+    if s == "mix" {
+        return Ok(Channel::Mix);
+    }
+    s.parse()
+        .map(Channel::Index)
+        .map_err(|_| format!("expected a channel index or \"mix\", got \"{s}\""))
+}
+
+fn parse_amplitude(s: &str) -> Result<f32, String> {
+    let amplitude: f32 = s
+        .parse()
+        .map_err(|_| format!("invalid amplitude \"{s}\""))?;
+    if !(0.0..=1.0).contains(&amplitude) {
+        return Err(format!(
+            "amplitude must be between 0 and 1, got {amplitude}"
+        ));
+    }
+    Ok(amplitude)
+}
+
+fn parse_bits(s: &str) -> Result<WavFormat, String> {
+    match s {
+        "32" => Ok(WavFormat::Float32),
+        "16" => Ok(WavFormat::Int16),
+        _ => Err(format!("expected 16 or 32 bits per sample, got \"{s}\"")),
+    }
+}
+
+fn parse_peak_threshold(s: &str) -> Result<f32, String> {
+    let threshold: f32 = s
+        .parse()
+        .map_err(|_| format!("invalid peak threshold \"{s}\""))?;
+    if !(0.0..1.0).contains(&threshold) {
+        return Err(format!(
+            "peak threshold must be at least 0 and below 1, got {threshold}"
+        ));
+    }
+    Ok(threshold)
+}
+
+#[cfg(feature = "listen")]
+fn parse_silence_threshold(s: &str) -> Result<f32, String> {
+    let threshold: f32 = s
+        .parse()
+        .map_err(|_| format!("invalid silence threshold \"{s}\""))?;
+    if !threshold.is_finite() || threshold < 0.0 {
+        return Err(format!(
+            "silence threshold must be a non-negative RMS, got {threshold}"
+        ));
+    }
+    Ok(threshold)
+}
+
+/// Bytes given on the command line as a hex string
+#[derive(Clone, Debug)]
+pub struct Hex(pub Vec<u8>);
+
+pub fn parse_hex(s: &str) -> Result<Hex, String> {
+    if !s.len().is_multiple_of(2) {
+        return Err(format!(
+            "hex string has an odd number of digits ({})",
+            s.len()
+        ));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("invalid hex digits at position {i}"))
+        })
+        .collect::<Result<_, _>>()
+        .map(Hex)
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum BandOption {
+    /// 1875 Hz to ~6.3 kHz
+    Audible,
+    /// 15 kHz to ~18 kHz
+    NearUltrasonic,
+    /// 18.75 kHz to ~21.7 kHz, needs 48 kHz and capable speakers/mics
+    Ultrasonic,
+}
+
+impl From<BandOption> for Band {
+    fn from(band: BandOption) -> Self {
+        match band {
+            BandOption::Audible => Band::Audible,
+            BandOption::NearUltrasonic => Band::NearUltrasonic,
+            BandOption::Ultrasonic => Band::Ultrasonic,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FecOption {
+    /// no error correction
+    None,
+    /// Hamming(7,4), corrects a flipped bit in every 7, 75% more bits
+    Hamming74,
+}
+
+impl From<FecOption> for ErrorCorrection {
+    fn from(fec: FecOption) -> Self {
+        match fec {
+            FecOption::None => ErrorCorrection::None,
+            FecOption::Hamming74 => ErrorCorrection::Hamming74,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CipherOption {
+    /// AES-128-GCM-SIV, 16-byte keys
+    Aes128GcmSiv,
+    /// AES-256-GCM-SIV, 32-byte keys
+    Aes256GcmSiv,
+    /// ChaCha20-Poly1305, 32-byte keys, fast without AES hardware
+    Chacha20Poly1305,
+}
+
+impl From<CipherOption> for CipherKind {
+    fn from(cipher: CipherOption) -> Self {
+        match cipher {
+            CipherOption::Aes128GcmSiv => CipherKind::Aes128GcmSiv,
+            CipherOption::Aes256GcmSiv => CipherKind::Aes256GcmSiv,
+            CipherOption::Chacha20Poly1305 => CipherKind::ChaCha20Poly1305,
+        }
+    }
+}
+
 #[derive(Parser)]
 /// Arguments for DOSR
 pub struct Args {
@@ -51,6 +380,75 @@ pub struct Args {
     #[clap(long, default_value = "48000.0")]
     pub sample_rate: f32,
 
+    /// frequency band used for the tones
+    #[clap(long, value_enum, default_value = "audible")]
+    pub band: BandOption,
+
+    /// frequency of the lowest tone in Hz, shifting the band
+    #[clap(long)]
+    pub base_freq: Option<f32>,
+
+    /// spacing of the tones in Hz
+    #[clap(long)]
+    pub delta_freq: Option<f32>,
+
+    /// bits carried by every tone, which chooses between 2^bits tones
+    #[clap(long, value_parser = clap::value_parser!(u8).range(1..=8))]
+    pub bits_per_chunk: Option<u8>,
+
+    /// simultaneous tones of every frame
+    #[clap(long, value_parser = clap::value_parser!(u8).range(1..))]
+    pub chunks_per_frame: Option<u8>,
+
+    /// TOML file with the tone grid and framing (base_freq, delta_freq, bits_per_chunk,
+    /// chunks_per_frame, duration_s, sample_rate), instead of the band, duration and sample rate
+    #[clap(long, conflicts_with_all = [
+        "duration_ms",
+        "sample_rate",
+        "band",
+        "base_freq",
+        "delta_freq",
+        "bits_per_chunk",
+        "chunks_per_frame",
+    ])]
+    pub config: Option<String>,
+
+    /// associated data (hex) authenticated by the cipher, must match on both ends
+    #[clap(long, value_parser = parse_hex)]
+    pub aad: Option<Hex>,
+
+    /// start the payload with a header saying whether it's encrypted, must match on both ends
+    #[clap(long)]
+    pub header: bool,
+
+    /// send a chirp ahead of the frames for the decoder to line them up on, must match on both
+    /// ends
+    #[clap(long)]
+    pub preamble: bool,
+
+    /// precede the payload with its length, so trailing noise decodes to nothing, must match on
+    /// both ends
+    #[clap(long)]
+    pub length_prefix: bool,
+
+    /// append a CRC32 to the payload and check it when decoding, must match on both ends
+    #[clap(long)]
+    pub crc: bool,
+
+    /// error correction code protecting the payload, must match on both ends. The codes have a
+    /// fixed rate, there is no parity byte count to choose
+    #[clap(long, value_enum, default_value = "none")]
+    pub fec: FecOption,
+
+    /// cipher of the encryption options, whose keys and key files are as long as it needs, must
+    /// match on both ends
+    #[clap(long, value_enum, default_value = "aes128-gcm-siv")]
+    pub cipher: CipherOption,
+
+    /// callsign keyed in Morse code before the data, so a listener can identify the transmission
+    #[clap(long)]
+    pub morse_prefix: Option<String>,
+
     /// action to perform: encode, decode
     #[command(subcommand)]
     pub action: Action,
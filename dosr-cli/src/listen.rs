@@ -0,0 +1,104 @@
+use std::sync::mpsc;
+
+use anyhow::{Context, Result, anyhow};
+use cpal::{
+    FromSample, Sample, SampleFormat, SampleRate, SizedSample, Stream, StreamConfig,
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+};
+use dosr::Dosr;
+use itertools::Itertools;
+
+use crate::waterfall;
+
+/// Decodes the default input device until interrupted, printing every message as it ends, and a
+/// waterfall row for every frame-long block of samples if asked to
+pub fn listen(dosr: &Dosr, hex: bool, waterfall: bool, verbose: bool) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let stream = open_input(dosr.sample_rate(), sender)?;
+    stream.play().context("Failed to start recording")?;
+    eprintln!("Listening, press Ctrl-C to stop");
+    let mut block = Vec::with_capacity(dosr.samples_per_frame());
+    let mut blocks = 0;
+    let samples = receiver.into_iter().inspect(move |&sample| {
+        if !waterfall {
+            return;
+        }
+        block.push(sample);
+        if block.len() == dosr.samples_per_frame() {
+            for magnitudes in dosr.band_spectrogram(&block) {
+                waterfall::print_row(blocks, &magnitudes);
+            }
+            blocks += 1;
+            block.clear();
+        }
+    });
+    for (i, decoded) in dosr.decode_stream(samples).enumerate() {
+        match decoded {
+            Ok(decoded) if hex => {
+                println!("Decoded message {i}:\n{:02x}", decoded.iter().format(""))
+            }
+            Ok(decoded) => println!(
+                "Decoded message {i}:\n{}",
+                String::from_utf8_lossy(&decoded)
+            ),
+            Err(err) if verbose => eprintln!("Message {i}: {err}"),
+            Err(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Records the default input device at the sample rate, sending its samples mixed down to mono
+fn open_input(sample_rate: f32, sender: mpsc::Sender<f32>) -> Result<Stream> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .context("No input device available")?;
+    let rate = SampleRate(sample_rate as u32);
+    let supported = device
+        .supported_input_configs()
+        .context("Failed to query the input device")?
+        .find(|config| (config.min_sample_rate()..=config.max_sample_rate()).contains(&rate))
+        .ok_or_else(|| {
+            let default = device
+                .default_input_config()
+                .map(|config| format!(", its default is {} Hz", config.sample_rate().0))
+                .unwrap_or_default();
+            anyhow!(
+                "The input device can't record at {sample_rate} Hz{default}, pass --sample-rate to match it on both ends"
+            )
+        })?
+        .with_sample_rate(rate);
+    let config = supported.config();
+    match supported.sample_format() {
+        SampleFormat::F32 => build::<f32>(&device, &config, sender),
+        SampleFormat::I16 => build::<i16>(&device, &config, sender),
+        SampleFormat::U16 => build::<u16>(&device, &config, sender),
+        SampleFormat::I32 => build::<i32>(&device, &config, sender),
+        format => Err(anyhow!("Unsupported input sample format {format}")),
+    }
+}
+
+fn build<T: SizedSample>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    sender: mpsc::Sender<f32>,
+) -> Result<Stream>
+where
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                for frame in data.chunks(channels) {
+                    let sum = frame.iter().map(|s| f32::from_sample(*s)).sum::<f32>();
+                    // The receiver only hangs up when decoding stops
+                    let _ = sender.send(sum / channels as f32);
+                }
+            },
+            |err| eprintln!("Recording error: {err}"),
+            None,
+        )
+        .context("Failed to open the input stream")
+}
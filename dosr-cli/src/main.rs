@@ -1,121 +1,791 @@
-use std::time::{Duration, Instant};
-
-use aes_gcm_siv::{
-    AeadCore, Aes128GcmSiv, KeyInit, Nonce,
-    aead::{Aead, OsRng},
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufReader, BufWriter, Cursor, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
 };
-use anyhow::Result;
-use args::{Action, Args, Encryption};
+
+use aes_gcm_siv::aead::{OsRng, rand_core::RngCore};
+use anyhow::{Context, Result};
+use args::{Action, Args, Channel, Encryption, Hex, Keygen, parse_hex};
 use clap::Parser;
-use dosr::Dosr;
-use hound::{WavSpec, WavWriter};
+use dosr::{
+    Band, CipherKind, DecodeError, DecodeReport, Dosr, EncodedMessage, ErrorCorrection,
+    ModemConfig, WavFormat, downmix, extract_channel, read_wav,
+};
 use itertools::Itertools;
-use k256::{Secp256k1, SecretKey, elliptic_curve::PublicKey, pkcs8::DecodePublicKey};
+use k256::{
+    Secp256k1, SecretKey,
+    elliptic_curve::PublicKey,
+    pkcs8::{DecodePublicKey, EncodePublicKey},
+};
 
 mod args;
+#[cfg(feature = "listen")]
+mod listen;
+mod waterfall;
 
 fn main() {
     let args = Args::parse();
     let duration = Duration::from_millis(args.duration_ms);
     let sample_rate = args.sample_rate;
-    let dosr = Dosr::default()
-        .with_duration_s(duration.as_secs_f32())
-        .with_sample_rate(sample_rate);
+    let band = Band::from(args.band);
+    let dosr = match &args.config {
+        Some(path) => load_config(path).unwrap_or_else(|err| {
+            eprintln!("{err:#}");
+            std::process::exit(1);
+        }),
+        None => {
+            let retuned = args.base_freq.is_some()
+                || args.delta_freq.is_some()
+                || args.bits_per_chunk.is_some()
+                || args.chunks_per_frame.is_some();
+            // A retuned grid is only checked against the Nyquist frequency once it's complete
+            if !retuned && sample_rate < band.min_sample_rate() {
+                eprintln!(
+                    "The {:?} band requires a sample rate of at least {} Hz",
+                    band,
+                    band.min_sample_rate()
+                );
+                std::process::exit(1);
+            }
+            let dosr = Dosr::default()
+                .with_band(band)
+                .with_duration_s(duration.as_secs_f32())
+                .with_sample_rate(sample_rate);
+            retune(dosr, &args)
+        }
+    };
+    let dosr = dosr
+        .with_header(args.header)
+        .with_preamble(args.preamble)
+        .with_length_prefix(args.length_prefix)
+        .with_crc(args.crc)
+        .with_error_correction(args.fec.into());
+    let cipher = CipherKind::from(args.cipher);
+    if let Err(err) = dosr.validate() {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+    let dosr = match &args.aad {
+        Some(aad) => dosr.with_aad(&aad.0),
+        None => dosr,
+    };
+    let dosr = match &args.morse_prefix {
+        Some(callsign) => dosr.try_with_morse_prefix(callsign).unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }),
+        None => dosr,
+    };
 
+    // Keep the sum of the simultaneous tones from clipping by default
+    let default_amplitude = 1.0 / dosr.chunks_per_frame() as f32;
     match args.action {
         Action::Encode {
             message,
+            hex,
             output_path,
+            split_frames,
+            amplitude,
+            normalize,
+            repeat,
+            gap_frames,
+            bits,
             encryption_options,
-        } => encode(
-            &message,
-            &output_path,
-            &encryption_options,
-            &dosr,
-            args.verbose,
-        ),
+        } => {
+            let amplitude = amplitude.unwrap_or(default_amplitude);
+            let dosr = dosr.with_amplitude(amplitude).with_normalization(normalize);
+            encode(
+                &message_bytes(message, hex),
+                &output_path,
+                split_frames.as_deref(),
+                Repeat {
+                    count: repeat as usize,
+                    gap_frames,
+                },
+                bits,
+                &with_encryption(dosr, &encryption_options, cipher),
+                args.verbose,
+            )
+            .unwrap_or_else(|err| {
+                eprintln!("{err:#}");
+                std::process::exit(1);
+            })
+        }
+        Action::BatchEncode {
+            input,
+            output_dir,
+            hex,
+            encryption_options,
+        } => {
+            let encryption = match &encryption_options {
+                None => "none",
+                Some(Encryption::Sym { .. }) => "sym",
+                Some(Encryption::Asym { .. }) => "asym",
+            };
+            let grid = match &args.config {
+                Some(path) => path.clone(),
+                None => format!("{band:?}"),
+            };
+            let config = format!(
+                "{},{},{},{}",
+                dosr.duration_s() * 1000.0,
+                dosr.sample_rate(),
+                csv_field(&grid),
+                encryption
+            );
+            let dosr = with_encryption(
+                dosr.with_amplitude(default_amplitude),
+                &encryption_options,
+                cipher,
+            );
+            batch_encode(&input, &output_dir, hex, &config, &dosr, args.verbose).unwrap_or_else(
+                |err| {
+                    eprintln!("Failed to batch encode: {err:#}");
+                    std::process::exit(1);
+                },
+            )
+        }
         Action::Decode {
             input_path,
+            diagnostics_csv,
+            stats,
+            channel,
+            hex,
+            all,
+            peak_threshold,
+            waterfall,
+            spectrogram_fft_size,
+            encryption_options,
+        } => {
+            let samples = read_input(&input_path, channel, args.verbose).unwrap_or_else(|err| {
+                eprintln!("{err:#}");
+                std::process::exit(1);
+            });
+            let dosr = dosr.with_peak_threshold(peak_threshold);
+            if waterfall {
+                let fft_size = spectrogram_fft_size.unwrap_or(dosr.samples_per_frame());
+                if fft_size < dosr.samples_per_frame() {
+                    eprintln!(
+                        "The spectrogram FFT size must be at least {} samples, a frame",
+                        dosr.samples_per_frame()
+                    );
+                    std::process::exit(1);
+                }
+                waterfall::print(&dosr.band_spectrogram_with_fft_size(&samples, fft_size));
+            }
+            if let Err(err) = decode(
+                &samples,
+                diagnostics_csv.as_deref(),
+                stats,
+                hex,
+                all,
+                &with_encryption(dosr, &encryption_options, cipher),
+                args.verbose,
+            ) {
+                eprintln!("{err:#}");
+                std::process::exit(1);
+            }
+        }
+        Action::Diff {
+            expected,
+            input_path,
+            hex,
+            channel,
+            encryption_options,
+        } => {
+            let samples = read_input(&input_path, channel, args.verbose).unwrap_or_else(|err| {
+                eprintln!("{err:#}");
+                std::process::exit(1);
+            });
+            // A fresh nonce changes every chunk, so only the plaintext can be compared then
+            let compare_chunks = encryption_options.is_none();
+            diff(
+                &message_bytes(expected, hex),
+                &samples,
+                &with_encryption(dosr, &encryption_options, cipher),
+                compare_chunks,
+            )
+        }
+        Action::Keygen { kind } => keygen(&kind, cipher).unwrap_or_else(|err| {
+            eprintln!("Failed to generate keys: {err}");
+            std::process::exit(1);
+        }),
+        #[cfg(feature = "play")]
+        Action::Play {
+            message,
+            hex,
+            amplitude,
+            repeat,
+            looped,
+            gap_frames,
             encryption_options,
-        } => decode(&input_path, &encryption_options, &dosr, args.verbose),
+        } => {
+            let dosr = dosr.with_amplitude(amplitude.unwrap_or(default_amplitude));
+            let dosr = with_encryption(dosr, &encryption_options, cipher);
+            let repeat = Repeat {
+                count: repeat as usize,
+                gap_frames,
+            };
+            let mut message = EncodedMessage {
+                samples: repeated(
+                    dosr.encode_data(&message_bytes(message, hex)),
+                    &repeat,
+                    &dosr,
+                ),
+                config: &dosr,
+            };
+            if looped {
+                // The gap also separates every pass from the next one
+                let len = message.samples.len() + gap_frames * dosr.samples_per_frame();
+                message.samples.resize(len, 0.0);
+            }
+            loop {
+                if let Err(err) = message.play(None) {
+                    eprintln!("{err}");
+                    std::process::exit(1);
+                }
+                if !looped {
+                    break;
+                }
+            }
+        }
+        #[cfg(feature = "listen")]
+        Action::Listen {
+            hex,
+            peak_threshold,
+            silence_threshold,
+            waterfall,
+            encryption_options,
+        } => {
+            if !args.preamble {
+                eprintln!(
+                    "Warning: without --preamble the frames only line up with transmissions that start on a frame boundary"
+                );
+            }
+            let dosr = dosr
+                .with_peak_threshold(peak_threshold)
+                .with_silence_threshold(silence_threshold);
+            if let Err(err) = listen::listen(
+                &with_encryption(dosr, &encryption_options, cipher),
+                hex,
+                waterfall,
+                args.verbose,
+            ) {
+                eprintln!("{err:#}");
+                std::process::exit(1);
+            }
+        }
+        #[cfg(feature = "serde")]
+        Action::Explain { message, hex } => {
+            let explanation = dosr.explain(&message_bytes(message, hex));
+            let json = serde_json::to_string_pretty(&explanation).expect("Failed to serialize");
+            println!("{json}");
+        }
+    }
+}
+
+/// Overrides the tone grid of the band with the one given on the command line
+fn retune(mut dosr: Dosr, args: &Args) -> Dosr {
+    if let Some(base_freq) = args.base_freq {
+        dosr = dosr.with_base_freq(base_freq);
+    }
+    if let Some(delta_freq) = args.delta_freq {
+        dosr = dosr.with_delta_freq(delta_freq);
+    }
+    if let Some(bits) = args.bits_per_chunk {
+        dosr = dosr.with_bits_per_chunk(bits as usize);
+    }
+    if let Some(chunks) = args.chunks_per_frame {
+        dosr = dosr.with_chunks_per_frame(chunks as usize);
     }
+    dosr
+}
+
+/// Reads the modem configuration from a TOML file
+fn load_config(path: &str) -> Result<Dosr> {
+    let config = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the configuration {path}"))?;
+    let config: ModemConfig =
+        toml::from_str(&config).with_context(|| format!("Invalid configuration {path}"))?;
+    Dosr::try_from(config).with_context(|| format!("Invalid configuration {path}"))
+}
+
+fn with_encryption(
+    dosr: Dosr,
+    encryption_options: &Option<Encryption>,
+    cipher: CipherKind,
+) -> Dosr {
+    match create_key(encryption_options, cipher) {
+        Ok(Some(key)) => dosr.with_key(cipher, &key),
+        Ok(None) => dosr,
+        Err(err) => {
+            eprintln!("Failed to create cipher: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn message_bytes(message: String, hex: bool) -> Vec<u8> {
+    if !hex {
+        return message.into_bytes();
+    }
+    match parse_hex(&message) {
+        Ok(Hex(bytes)) => bytes,
+        Err(err) => {
+            eprintln!("Invalid hex message: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// How many times to transmit the message, and the silence between the transmissions
+struct Repeat {
+    count: usize,
+    gap_frames: usize,
 }
 
 fn encode(
-    message: &str,
+    data: &[u8],
     output_path: &str,
-    encryption_options: &Option<Encryption>,
+    split_frames: Option<&str>,
+    repeat: Repeat,
+    format: WavFormat,
     dosr: &Dosr,
     verbose: bool,
-) {
-    let data = message.as_bytes().to_vec();
-    let start = Instant::now();
-    let data =
-        if let Some(cipher) = create_cipher(encryption_options).expect("Failed to create cipher") {
-            let nonce = Aes128GcmSiv::generate_nonce(&mut OsRng);
-            let encrypted = cipher.encrypt(&nonce, data.as_ref()).unwrap();
-            [nonce.to_vec(), encrypted].concat()
-        } else {
-            data
-        };
-    let encryption_time = start.elapsed();
+) -> Result<()> {
     let start = Instant::now();
-    let samples = dosr.encode_data(&data);
+    let samples = if let Some(split_frames) = split_frames {
+        let frames = dosr.encode_labeled_frames(data);
+        write_frames(split_frames, &frames, format, dosr).context("Failed to write frames")?;
+        frames
+            .into_iter()
+            .flat_map(|(_, samples)| samples)
+            .collect_vec()
+    } else {
+        dosr.encode_data(data)
+    };
+    let message = EncodedMessage {
+        samples: repeated(samples, &repeat, dosr),
+        config: dosr,
+    };
     let encoding_time = start.elapsed();
     if verbose {
         eprintln!("Encoding time: {:?}", encoding_time);
-        eprintln!("Encryption time: {:?}", encryption_time);
     }
 
-    let spec = WavSpec {
-        channels: 1,
-        sample_rate: dosr.sample_rate() as u32,
-        bits_per_sample: 32,
-        sample_format: hound::SampleFormat::Float,
+    write_wav(output_path, &message, format).context("Failed to write output file")
+}
+
+/// Concatenates the repetitions of the message with the silent gaps between them, so that
+/// `decode --all` can tell them apart
+fn repeated(samples: Vec<f32>, repeat: &Repeat, dosr: &Dosr) -> Vec<f32> {
+    if repeat.count == 1 {
+        return samples;
+    }
+    let gap = vec![0.0; repeat.gap_frames * dosr.samples_per_frame()];
+    Itertools::intersperse(std::iter::repeat_n(samples, repeat.count), gap)
+        .flatten()
+        .collect_vec()
+}
+
+/// Encodes every message of the input into its own WAV file, along with a manifest of the
+/// inputs and configuration of every output
+fn batch_encode(
+    input: &str,
+    output_dir: &str,
+    hex: bool,
+    config: &str,
+    dosr: &Dosr,
+    verbose: bool,
+) -> Result<()> {
+    let input = Path::new(input);
+    let messages = if input.is_dir() {
+        let mut paths = std::fs::read_dir(input)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .filter_ok(|path| path.is_file())
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                let bytes = std::fs::read(&path)?;
+                let bytes = match hex {
+                    true => message_bytes(String::from_utf8(bytes)?.trim().to_string(), true),
+                    false => bytes,
+                };
+                Ok((path.display().to_string(), bytes))
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        std::fs::read_to_string(input)?
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let label = format!("{}:{}", input.display(), i + 1);
+                (label, message_bytes(line.to_string(), hex))
+            })
+            .collect_vec()
     };
 
-    let mut writer = WavWriter::create(output_path, spec).expect("Failed to create output file");
-    samples.iter().for_each(|s| {
-        writer.write_sample(*s).expect("Failed to write sample");
-    });
-    writer.finalize().expect("Failed to finalize output file");
+    let output_dir = Path::new(output_dir);
+    std::fs::create_dir_all(output_dir)?;
+    let mut manifest = BufWriter::new(File::create(output_dir.join("manifest.csv"))?);
+    writeln!(
+        manifest,
+        "file,input,duration_ms,sample_rate,band,encryption"
+    )?;
+    for (i, (label, message)) in messages.iter().enumerate() {
+        let file_name = format!("{i:04}.wav");
+        let repeat = Repeat {
+            count: 1,
+            gap_frames: 0,
+        };
+        let output_path = output_dir.join(&file_name);
+        encode(
+            message,
+            &output_path.to_string_lossy(),
+            None,
+            repeat,
+            WavFormat::Float32,
+            dosr,
+            verbose,
+        )?;
+        writeln!(manifest, "{file_name},{},{config}", csv_field(label))?;
+    }
+    manifest.flush()?;
+    Ok(())
 }
 
-fn decode(input_path: &str, encryption_options: &Option<Encryption>, dosr: &Dosr, verbose: bool) {
-    let samples = hound::WavReader::open(input_path)
-        .expect("Failed to open input file")
-        .samples()
-        .flatten()
-        .collect_vec();
+/// Quotes a field of a CSV file holding a separator, a quote or a line break, doubling its quotes
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Writes the message to a WAV file, or to stdout if the path is "-"
+fn write_wav(path: impl AsRef<Path>, message: &EncodedMessage, format: WavFormat) -> Result<()> {
+    if path.as_ref() != Path::new("-") {
+        return Ok(message.save_as(path, format)?);
+    }
+    // The WAV header is patched once the length is known, which needs a seekable writer
+    let mut wav = Cursor::new(vec![]);
+    message.write_wav_as(&mut wav, format)?;
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(wav.get_ref())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Writes every frame to its own WAV file, along with a manifest of the chunk values they carry
+fn write_frames(
+    dir: &str,
+    frames: &[(Vec<u8>, Vec<f32>)],
+    format: WavFormat,
+    dosr: &Dosr,
+) -> Result<()> {
+    let dir = Path::new(dir);
+    std::fs::create_dir_all(dir)?;
+    let mut manifest = BufWriter::new(File::create(dir.join("manifest.csv"))?);
+    writeln!(manifest, "file,values")?;
+    for (i, (values, samples)) in frames.iter().enumerate() {
+        let file_name = format!("frame_{i:04}.wav");
+        let message = EncodedMessage {
+            samples: samples.clone(),
+            config: dosr,
+        };
+        write_wav(dir.join(&file_name), &message, format)?;
+        writeln!(manifest, "{},{}", file_name, values.iter().join(" "))?;
+    }
+    manifest.flush()?;
+    Ok(())
+}
+
+/// Reads the channel to decode from the input file
+fn read_input(input_path: &str, channel: Channel, verbose: bool) -> Result<Vec<f32>> {
+    let (samples, spec) = if input_path == "-" {
+        // Pipes can't seek, so buffer the whole stream in memory first
+        let mut wav = vec![];
+        std::io::stdin()
+            .read_to_end(&mut wav)
+            .context("Failed to read stdin")?;
+        read_wav(Cursor::new(wav)).context("Failed to read stdin as WAV")?
+    } else {
+        let file = File::open(input_path)
+            .with_context(|| format!("Failed to open input file {input_path}"))?;
+        read_wav(BufReader::new(file))
+            .with_context(|| format!("Failed to read input file {input_path}"))?
+    };
+    let channels = spec.channels;
+    if verbose && channels > 1 {
+        eprintln!("Input has {channels} channels, decoding {channel:?}");
+    }
+    select_channel(samples, channels, channel)
+}
+
+fn decode(
+    samples: &[f32],
+    diagnostics_csv: Option<&str>,
+    stats: bool,
+    hex: bool,
+    all: bool,
+    dosr: &Dosr,
+    verbose: bool,
+) -> Result<()> {
+    if all {
+        decode_all(samples, hex, dosr);
+        return Ok(());
+    }
     let start = Instant::now();
-    let decoded = dosr.decode(&samples);
+    let (decoded, report) = dosr.decode_with_metrics(samples);
+    if let Some(diagnostics_csv) = diagnostics_csv {
+        write_diagnostics(diagnostics_csv, &report).context("Failed to write diagnostics")?;
+    }
+    if stats {
+        print_stats(&report, dosr.chunks_per_frame());
+    }
     let decoding_time = start.elapsed();
-    let start = Instant::now();
-    let decoded =
-        if let Some(cipher) = create_cipher(encryption_options).expect("Failed to create cipher") {
-            let nonce = decoded.iter().take(12).cloned().collect_vec();
-            let encrypted = decoded.into_iter().skip(12).collect_vec();
-            let nonce = Nonce::from_slice(&nonce);
-            cipher.decrypt(nonce, encrypted.as_ref()).unwrap()
-        } else {
-            decoded
-        };
-    let decryption_time = start.elapsed();
     if verbose {
         eprintln!("Decoding time: {:?}", decoding_time);
-        eprintln!("Decryption time: {:?}", decryption_time);
     }
-    let decoded = String::from_utf8(decoded).expect("Failed to decode message");
+    if report.truncated {
+        eprintln!(
+            "Warning: the recording ends partway through a frame after {} complete ones, it may have been cut off",
+            report.complete_frames
+        );
+    }
+    // Also reported when the payload fails its checks, which may be why it couldn't be fixed
+    if verbose && dosr.error_correction() != ErrorCorrection::None {
+        eprintln!("Error correction fixed {} bits", report.corrected_bits);
+    }
+    let decoded = decoded.inspect_err(|err| {
+        if let DecodeError::AuthFailed { raw }
+        | DecodeError::InvalidHeader { raw }
+        | DecodeError::ChecksumMismatch { raw } = err
+        {
+            eprintln!("Demodulated bytes: {:02x}", raw.iter().format(""));
+        }
+    })?;
+    if verbose && dosr.has_crc() {
+        eprintln!("CRC32 validated");
+    }
+    if hex {
+        println!("Decoded message:\n{:02x}", decoded.iter().format(""));
+        return Ok(());
+    }
+    let decoded = String::from_utf8(decoded).unwrap_or_else(|err| {
+        eprintln!("Warning: the message isn't valid UTF-8, showing it with the invalid bytes replaced, try --hex");
+        String::from_utf8_lossy(err.as_bytes()).into_owned()
+    });
     println!("Decoded message:\n{decoded}");
+    Ok(())
+}
+
+/// Decodes and prints every message, carrying on past the ones that fail
+fn decode_all(samples: &[f32], hex: bool, dosr: &Dosr) {
+    let results = dosr.decode_all(samples);
+    let mut failed = false;
+    for (i, decoded) in results.into_iter().enumerate() {
+        let message = match decoded {
+            Ok(decoded) if hex => Ok(format!("{:02x}", decoded.iter().format(""))),
+            Ok(decoded) => {
+                String::from_utf8(decoded).map_err(|_| "not valid UTF-8, try --hex".to_string())
+            }
+            Err(err) => Err(err.to_string()),
+        };
+        match message {
+            Ok(message) => println!("Decoded message {i}:\n{message}"),
+            Err(err) => {
+                eprintln!("Message {i}: {err}");
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
 }
 
-fn create_cipher(encryption_options: &Option<Encryption>) -> Result<Option<Aes128GcmSiv>> {
+/// Prints the byte and bit error rates of the decoded message against the expected one, the
+/// positions of the wrong bytes and the chunks of the frames that were received wrong
+fn diff(expected: &[u8], samples: &[f32], dosr: &Dosr, compare_chunks: bool) {
+    let (decoded, report) = dosr.decode_with_metrics(samples);
+    let decoded = match decoded {
+        Ok(decoded) => decoded,
+        Err(
+            DecodeError::AuthFailed { raw }
+            | DecodeError::InvalidHeader { raw }
+            | DecodeError::ChecksumMismatch { raw },
+        ) => {
+            println!("Decoding failed, comparing the demodulated bytes");
+            raw
+        }
+        Err(err) => {
+            println!("Decoding failed: {err}");
+            vec![]
+        }
+    };
+    let len = expected.len().max(decoded.len());
+    let (mut wrong_bytes, mut wrong_bits) = (vec![], 0);
+    for i in 0..len {
+        match (expected.get(i), decoded.get(i)) {
+            (Some(e), Some(d)) if e == d => continue,
+            (Some(e), Some(d)) => wrong_bits += (e ^ d).count_ones() as usize,
+            // Missing and extra bytes count as wrong in full
+            _ => wrong_bits += 8,
+        }
+        wrong_bytes.push(i);
+    }
+    let rate = |errors: usize, total: usize| 100.0 * errors as f32 / total.max(1) as f32;
+    println!(
+        "Bytes: {} expected, {} decoded",
+        expected.len(),
+        decoded.len()
+    );
+    println!(
+        "Byte errors: {} ({:.2}%)",
+        wrong_bytes.len(),
+        rate(wrong_bytes.len(), expected.len())
+    );
+    println!(
+        "Bit errors: {} ({:.2}%)",
+        wrong_bits,
+        rate(wrong_bits, 8 * expected.len())
+    );
+    if !wrong_bytes.is_empty() {
+        println!("Wrong bytes at: {}", wrong_bytes.iter().join(" "));
+    }
+    if !compare_chunks {
+        return;
+    }
+    let frames = dosr.explain(expected).frames;
+    for (i, (sent, received)) in frames.iter().zip(&report.frames).enumerate() {
+        let wrong_chunks = sent
+            .iter()
+            .enumerate()
+            .filter(|(j, chunk)| received.values.get(*j) != Some(chunk))
+            .map(|(j, chunk)| match received.values.get(j) {
+                Some(value) => format!("{j} (sent {chunk}, got {value})"),
+                None => format!("{j} (sent {chunk}, missed)"),
+            })
+            .collect_vec();
+        if !wrong_chunks.is_empty() {
+            println!("Frame {i}: wrong chunks {}", wrong_chunks.join(", "));
+        }
+    }
+    if frames.len() != report.frames.len() {
+        println!(
+            "Frames: {} sent, {} received",
+            frames.len(),
+            report.frames.len()
+        );
+    }
+}
+
+/// De-interleaves the samples of a multi-channel file into a single stream
+fn select_channel(samples: Vec<f32>, channels: u16, channel: Channel) -> Result<Vec<f32>> {
+    if channels == 1 {
+        return Ok(samples);
+    }
+    let channels = channels as usize;
+    match channel {
+        Channel::Index(index) if index as usize >= channels => Err(anyhow::anyhow!(
+            "Channel {index} requested but the input only has {channels} channels"
+        )),
+        Channel::Index(index) => Ok(extract_channel(&samples, channels, index as usize)),
+        Channel::Mix => Ok(downmix(&samples, channels)),
+    }
+}
+
+/// Prints the signal quality of every frame and of the weakest one to stderr
+fn print_stats(report: &DecodeReport, chunks_per_frame: usize) {
+    for (i, frame) in report.frames.iter().enumerate() {
+        eprintln!(
+            "Frame {i}: {}/{chunks_per_frame} tones, SNR {:.1} dB{}",
+            frame.detected,
+            frame.snr_db,
+            if frame.low_confidence {
+                " (low confidence)"
+            } else {
+                ""
+            }
+        );
+    }
+    eprintln!(
+        "Frames with unexpected tone counts: {}",
+        report.unexpected_tone_frames()
+    );
+    match report.min_snr_db() {
+        Some(snr_db) => eprintln!("Minimum SNR: {snr_db:.1} dB"),
+        None => eprintln!("Minimum SNR: no tones received"),
+    }
+}
+
+fn write_diagnostics(path: &str, report: &DecodeReport) -> Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "frame,detected,values,snr_db,low_confidence")?;
+    for (i, frame) in report.frames.iter().enumerate() {
+        writeln!(
+            file,
+            "{},{},{},{:.2},{}",
+            i,
+            frame.detected,
+            frame.values.iter().join(" "),
+            frame.snr_db,
+            frame.low_confidence
+        )?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// Writes new key files in the formats the encryption options read, never overwriting a file
+fn keygen(kind: &Keygen, cipher: CipherKind) -> Result<()> {
+    match kind {
+        Keygen::Sym { key_path } => {
+            let mut key = vec![0u8; cipher.key_len()];
+            OsRng.fill_bytes(&mut key);
+            File::create_new(key_path)?.write_all(&key)?;
+        }
+        Keygen::Asym {
+            private_key_path,
+            public_key_path,
+        } => {
+            let private_key = SecretKey::random(&mut OsRng);
+            let public_key = private_key.public_key().to_public_key_der()?;
+            File::create_new(private_key_path)?.write_all(&private_key.to_sec1_der()?)?;
+            File::create_new(public_key_path)?.write_all(public_key.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Key of the cipher read from the key file or derived from the key pair, if encrypting
+fn create_key(
+    encryption_options: &Option<Encryption>,
+    cipher: CipherKind,
+) -> Result<Option<Vec<u8>>> {
     let Some(encryption_options) = encryption_options else {
         return Ok(None);
     };
 
     let key = match encryption_options {
-        Encryption::Sym { key_path } => std::fs::read(key_path)?,
+        Encryption::Sym { key_path } => {
+            let key = std::fs::read(key_path)?;
+            if key.len() != cipher.key_len() {
+                anyhow::bail!(
+                    "the key file {key_path} is {} bytes long, expected {} for {cipher:?}",
+                    key.len(),
+                    cipher.key_len()
+                );
+            }
+            key
+        }
         Encryption::Asym {
             private_key_path,
             public_key_path,
@@ -125,21 +795,14 @@ fn create_cipher(encryption_options: &Option<Encryption>) -> Result<Option<Aes12
             let public_key = PublicKey::<Secp256k1>::read_public_key_der_file(public_key_path)?;
             let secret =
                 k256::ecdh::diffie_hellman(private_key.to_nonzero_scalar(), public_key.as_affine());
-            let mut key = vec![0u8; 16];
+            let mut key = vec![0u8; cipher.key_len()];
             secret
                 .extract::<k256::sha2::Sha256>(None)
                 .expand(&[], &mut key)
-                .map_err(|err| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        format!("Failed to expand key: {}", err),
-                    )
-                })?;
+                .map_err(|err| std::io::Error::other(format!("Failed to expand key: {}", err)))?;
             key
         }
     };
 
-    let cipher = Aes128GcmSiv::new_from_slice(&key)
-        .expect("Failed to create cipher, the key should be 16 bytes long");
-    Ok(Some(cipher))
+    Ok(Some(key))
 }
@@ -0,0 +1,39 @@
+use itertools::Itertools;
+
+/// Shades from the weakest to the strongest magnitude
+const SHADES: &[u8] = b" .:-=+*#%@";
+/// Number of columns of the display
+const WIDTH: usize = 96;
+
+/// Prints a row of shades per frame, from the lowest to the highest frequency of the band
+pub fn print(spectrogram: &[Vec<f32>]) {
+    for (i, magnitudes) in spectrogram.iter().enumerate() {
+        print_row(i, magnitudes);
+    }
+}
+
+/// Prints the row of the frame with the given index
+pub fn print_row(index: usize, magnitudes: &[f32]) {
+    eprintln!("{index:>5} |{}|", row(magnitudes));
+}
+
+/// Fits the magnitudes into the width, keeping the strongest of the bins behind each column
+fn row(magnitudes: &[f32]) -> String {
+    if magnitudes.is_empty() {
+        return String::new();
+    }
+    let columns = WIDTH.min(magnitudes.len());
+    (0..columns)
+        .map(|column| {
+            let bins =
+                column * magnitudes.len() / columns..(column + 1) * magnitudes.len() / columns;
+            let magnitude = magnitudes[bins]
+                .iter()
+                .cloned()
+                .filter(|m| m.is_finite())
+                .fold(0.0f32, f32::max);
+            let shade = (magnitude.clamp(0.0, 1.0) * (SHADES.len() - 1) as f32).round() as usize;
+            SHADES[shade] as char
+        })
+        .join("")
+}
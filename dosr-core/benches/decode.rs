@@ -0,0 +1,36 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use dosr::{DetectionMethod, Dosr};
+
+type Config = (&'static str, fn() -> Dosr);
+
+/// The default grid of 96 tones, and a grid of 8 tones in frames whose length isn't a power of
+/// two, where the Goertzel filters pay off
+fn configs() -> [Config; 2] {
+    [
+        ("default", Dosr::default),
+        ("small_grid", || {
+            Dosr::new(3000.0, 50.0, 2, 4, 0.05, 44100.0)
+        }),
+    ]
+}
+
+fn decode(c: &mut Criterion) {
+    let message = vec![0xa5; 1024];
+    let mut group = c.benchmark_group("decode");
+    group.sample_size(10);
+    for (name, config) in configs() {
+        let samples = config().encode_data(&message);
+        for method in [DetectionMethod::Fft, DetectionMethod::Goertzel] {
+            let dosr = config().with_detection_method(method);
+            group.bench_with_input(
+                BenchmarkId::new(name, format!("{method:?}")),
+                &samples,
+                |b, samples| b.iter(|| dosr.decode(black_box(samples))),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use dosr::Dosr;
+
+/// Compare with and without the `oscillator` feature to measure the sine generation
+fn encode(c: &mut Criterion) {
+    let dosr = Dosr::default();
+    let mut group = c.benchmark_group("encode_data");
+    for len in [16, 256] {
+        let message = vec![0xa5; len];
+        group.bench_with_input(BenchmarkId::from_parameter(len), &message, |b, message| {
+            b.iter(|| dosr.encode_data(black_box(message)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
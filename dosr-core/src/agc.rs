@@ -0,0 +1,59 @@
+/// Automatic gain control applied to each frame before detection
+///
+/// A running level estimate is tracked across frames and every frame is scaled
+/// so that level matches `target_rms`, keeping magnitudes comparable across a
+/// recording whose level fades in and out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Agc {
+    /// RMS level every frame is scaled towards
+    target_rms: f32,
+    /// Fraction of a level increase followed per frame, in (0, 1]
+    attack: f32,
+    /// Fraction of a level decrease followed per frame, in (0, 1]
+    release: f32,
+}
+
+impl Default for Agc {
+    fn default() -> Self {
+        Self {
+            target_rms: 0.5,
+            attack: 0.5,
+            release: 0.2,
+        }
+    }
+}
+
+impl Agc {
+    pub fn new(target_rms: f32, attack: f32, release: f32) -> Self {
+        Self {
+            target_rms,
+            attack: attack.clamp(f32::EPSILON, 1.0),
+            release: release.clamp(f32::EPSILON, 1.0),
+        }
+    }
+}
+
+/// Level tracking state of an [`Agc`] over a single decode
+#[derive(Debug, Default)]
+pub(crate) struct AgcState {
+    level: Option<f32>,
+}
+
+impl AgcState {
+    pub fn apply(&mut self, agc: &Agc, frame: &mut [f32]) {
+        if frame.is_empty() {
+            return;
+        }
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        let level = match self.level {
+            None => rms,
+            Some(level) if rms > level => level + agc.attack * (rms - level),
+            Some(level) => level + agc.release * (rms - level),
+        };
+        self.level = Some(level);
+        if level > 0.0 {
+            let gain = agc.target_rms / level;
+            frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{DEFAULT_BASE_FREQ, DEFAULT_DELTA_FREQ};
+
+/// Frequency band presets for the tone grid
+///
+/// The near-ultrasonic and ultrasonic bands need speakers and microphones that
+/// can actually reproduce and capture those frequencies, which many consumer
+/// devices roll off well before 20 kHz.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Band {
+    /// 1875 Hz to ~6.3 kHz, works on pretty much any device
+    #[default]
+    Audible,
+    /// 15 kHz to ~18 kHz, barely audible for most adults
+    NearUltrasonic,
+    /// 18.75 kHz to ~21.7 kHz, requires a 48 kHz sample rate
+    Ultrasonic,
+}
+
+impl Band {
+    /// Base frequency (Hz)
+    pub fn base_freq(&self) -> f32 {
+        match self {
+            Band::Audible => DEFAULT_BASE_FREQ,
+            Band::NearUltrasonic => 15000.0,
+            Band::Ultrasonic => 18750.0,
+        }
+    }
+
+    /// Frequency delta (Hz)
+    pub fn delta_freq(&self) -> f32 {
+        match self {
+            Band::Audible | Band::NearUltrasonic => DEFAULT_DELTA_FREQ,
+            Band::Ultrasonic => 2.0 * DEFAULT_DELTA_FREQ,
+        }
+    }
+
+    /// Number of chunks transmitted simultaneously
+    pub fn chunks_per_frame(&self) -> usize {
+        match self {
+            Band::Audible => 6,
+            Band::NearUltrasonic => 4,
+            Band::Ultrasonic => 2,
+        }
+    }
+
+    /// Lowest sample rate (Hz) that keeps the whole band clear of Nyquist
+    pub fn min_sample_rate(&self) -> f32 {
+        match self {
+            Band::Audible => 16000.0,
+            Band::NearUltrasonic => 44100.0,
+            Band::Ultrasonic => 48000.0,
+        }
+    }
+}
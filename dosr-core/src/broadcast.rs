@@ -0,0 +1,110 @@
+use itertools::Itertools;
+
+use crate::{
+    dosr::Dosr,
+    error::{ConfigError, DecodeError},
+};
+
+/// Number of leading chunks of every broadcast frame carrying its sequence tag
+const TAG_CHUNKS: usize = 2;
+
+/// Broadcasting functionality
+impl Dosr {
+    /// Encodes the data for a one-way broadcast, repeating it for the given number of cycles.
+    ///
+    /// The first chunks of every frame tag it with its position in the message and whether it's
+    /// the last one, so [`Dosr::decode_broadcast`] can start listening at any frame and put the
+    /// message together from the frames of consecutive cycles. Line coding isn't applied, since
+    /// a receiver joining mid-broadcast couldn't tell a frame from its complement.
+    ///
+    /// Fails if the frames have no chunks left for data after the tag, or if the message needs
+    /// more frames than the tags can number.
+    pub fn encode_broadcast(&self, data: &[u8], cycles: usize) -> Result<Vec<f32>, ConfigError> {
+        let data_chunks = self.chunks_per_frame().saturating_sub(TAG_CHUNKS);
+        if data_chunks == 0 {
+            return Err(ConfigError::BroadcastChunks {
+                chunks: self.chunks_per_frame(),
+                tag_chunks: TAG_CHUNKS,
+            });
+        }
+        let chunks = self.bytes_to_chunks(&self.seal(data));
+        let frames = chunks.chunks(data_chunks).collect_vec();
+        // Every tag numbers a frame and says whether it's the last one
+        let max_frames = self.symbols().pow(TAG_CHUNKS as u32) / 2;
+        if frames.len() > max_frames {
+            return Err(ConfigError::BroadcastTooLong {
+                frames: frames.len(),
+                max_frames,
+            });
+        }
+        let frames = frames
+            .iter()
+            .enumerate()
+            .map(|(seq, chunks)| {
+                let last = seq == frames.len() - 1;
+                let mut frame = self.tag(2 * seq + last as usize);
+                frame.extend_from_slice(chunks);
+                frame
+            })
+            .collect_vec();
+        let cycles = std::iter::repeat_n(frames, cycles).flatten();
+        Ok(self.modulate_frames(self.encode_frames(cycles).into_iter()))
+    }
+
+    /// Decodes a broadcast, which may start and end at any frame, once every frame of the
+    /// message has been received in at least one of the cycles
+    pub fn decode_broadcast(&self, samples: &[f32]) -> Result<Vec<u8>, DecodeError> {
+        let samples = self.condition_signal(samples);
+        let received = self.receive_payload(&samples);
+        let values = self.decode_frames(&received);
+        let symbols = self.symbols();
+        let tagged = self
+            .detect_levels(&received, values)
+            .into_iter()
+            .filter(|frame| frame.len() > TAG_CHUNKS)
+            .map(|frame| {
+                let tag = frame[..TAG_CHUNKS]
+                    .iter()
+                    .fold(0, |acc, d| acc * symbols + *d as usize);
+                (tag / 2, tag % 2 == 1, frame[TAG_CHUNKS..].to_vec())
+            })
+            .collect_vec();
+
+        // A corrupt tag can claim to be the last frame, so trust the most common claim
+        let total = tagged
+            .iter()
+            .filter(|(_, last, _)| *last)
+            .map(|(seq, _, _)| seq + 1)
+            .counts()
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(total, _)| total);
+        let Some(total) = total else {
+            let seen = tagged.iter().map(|(seq, _, _)| seq).unique().count();
+            return Err(DecodeError::IncompleteBroadcast { seen, total: None });
+        };
+        let mut slots = vec![None; total];
+        for (seq, _, chunks) in tagged {
+            if let Some(slot @ None) = slots.get_mut(seq) {
+                *slot = Some(chunks);
+            }
+        }
+        let seen = slots.iter().flatten().count();
+        if seen < total {
+            return Err(DecodeError::IncompleteBroadcast {
+                seen,
+                total: Some(total),
+            });
+        }
+        self.decrypt(self.chunks_to_bytes(slots.into_iter().flatten().flatten()))
+    }
+
+    /// Chunks of a sequence tag, most significant first
+    fn tag(&self, tag: usize) -> Vec<u8> {
+        let symbols = self.symbols();
+        (0..TAG_CHUNKS as u32)
+            .rev()
+            .map(|i| (tag / symbols.pow(i) % symbols) as u8)
+            .collect_vec()
+    }
+}
@@ -0,0 +1,22 @@
+use itertools::Itertools;
+
+/// Averages the interleaved samples of every channel down to mono. A final partial frame of
+/// samples is averaged over the channels it has.
+pub fn downmix(interleaved: &[f32], channels: usize) -> Vec<f32> {
+    assert!(channels > 0, "There must be at least a channel");
+    interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect_vec()
+}
+
+/// Samples of a single channel out of the interleaved samples of every channel
+pub fn extract_channel(interleaved: &[f32], channels: usize, channel: usize) -> Vec<f32> {
+    assert!(channel < channels, "Channel {channel} of only {channels}");
+    interleaved
+        .iter()
+        .skip(channel)
+        .step_by(channels)
+        .copied()
+        .collect_vec()
+}
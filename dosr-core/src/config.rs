@@ -0,0 +1,56 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{dosr::Dosr, error::ConfigError};
+
+/// Parameters of the tone grid and the framing, which both ends of a transmission must share,
+/// to save and share tuned modems.
+///
+/// The chunks carry whole bits, so the number of tones each one chooses from is always derived
+/// from `bits_per_chunk`. Building a [`Dosr`] from it validates it like [`Dosr::try_new`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct ModemConfig {
+    /// Frequency of the lowest tone (Hz)
+    pub base_freq: f32,
+    /// Spacing of the tones (Hz)
+    pub delta_freq: f32,
+    pub bits_per_chunk: usize,
+    pub chunks_per_frame: usize,
+    /// Duration of every frame (seconds)
+    pub duration_s: f32,
+    /// Sample rate (Hz)
+    pub sample_rate: f32,
+}
+
+impl TryFrom<ModemConfig> for Dosr {
+    type Error = ConfigError;
+
+    fn try_from(config: ModemConfig) -> Result<Self, Self::Error> {
+        Dosr::try_new(
+            config.base_freq,
+            config.delta_freq,
+            config.bits_per_chunk,
+            config.chunks_per_frame,
+            config.duration_s,
+            config.sample_rate,
+        )
+    }
+}
+
+impl Dosr {
+    /// Parameters of the modem, or `None` if its chunks choose between a number of tones that
+    /// isn't a power of two and so carry no whole number of bits
+    pub fn modem_config(&self) -> Option<ModemConfig> {
+        let values = self.values_per_chunk();
+        values.is_power_of_two().then(|| ModemConfig {
+            base_freq: self.base_freq(),
+            delta_freq: self.delta_freq(),
+            bits_per_chunk: values.trailing_zeros() as usize,
+            chunks_per_frame: self.chunks_per_frame(),
+            duration_s: self.duration_s(),
+            sample_rate: self.sample_rate(),
+        })
+    }
+}
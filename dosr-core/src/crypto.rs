@@ -0,0 +1,127 @@
+use std::fmt;
+
+use aes_gcm_siv::{
+    AeadCore, Aes128GcmSiv, Aes256GcmSiv, KeyInit,
+    aead::{Aead, Nonce, OsRng, Payload},
+};
+use chacha20poly1305::ChaCha20Poly1305;
+
+/// Length of the nonce prepended to every encrypted payload (bytes)
+pub const NONCE_LEN: usize = 12;
+/// Length of the authentication tag appended to every encrypted payload (bytes)
+const TAG_LEN: usize = 16;
+
+/// Authenticated ciphers the payload can be encrypted with. All of them take a 12-byte nonce and
+/// add a 16-byte tag, only their keys differ.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CipherKind {
+    #[default]
+    Aes128GcmSiv,
+    Aes256GcmSiv,
+    ChaCha20Poly1305,
+}
+
+impl CipherKind {
+    /// Length of the keys of the cipher (bytes)
+    pub fn key_len(self) -> usize {
+        match self {
+            CipherKind::Aes128GcmSiv => 16,
+            CipherKind::Aes256GcmSiv | CipherKind::ChaCha20Poly1305 => 32,
+        }
+    }
+}
+
+/// Authenticated cipher protecting the payload before modulation
+#[derive(Clone)]
+pub(crate) enum Cipher {
+    // The AES key schedules are large, boxed to keep the other variants small
+    Aes128GcmSiv(Box<Aes128GcmSiv>),
+    Aes256GcmSiv(Box<Aes256GcmSiv>),
+    ChaCha20Poly1305(ChaCha20Poly1305),
+}
+
+impl fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cipher({:?})", self.kind())
+    }
+}
+
+impl From<Aes128GcmSiv> for Cipher {
+    fn from(cipher: Aes128GcmSiv) -> Self {
+        Self::Aes128GcmSiv(Box::new(cipher))
+    }
+}
+
+impl Cipher {
+    /// Cipher of the kind keyed with the key, if it's as long as the kind's keys
+    pub fn new(kind: CipherKind, key: &[u8]) -> Option<Self> {
+        let cipher = match kind {
+            CipherKind::Aes128GcmSiv => {
+                Self::Aes128GcmSiv(Box::new(KeyInit::new_from_slice(key).ok()?))
+            }
+            CipherKind::Aes256GcmSiv => {
+                Self::Aes256GcmSiv(Box::new(KeyInit::new_from_slice(key).ok()?))
+            }
+            CipherKind::ChaCha20Poly1305 => {
+                Self::ChaCha20Poly1305(KeyInit::new_from_slice(key).ok()?)
+            }
+        };
+        Some(cipher)
+    }
+
+    pub fn kind(&self) -> CipherKind {
+        match self {
+            Self::Aes128GcmSiv(_) => CipherKind::Aes128GcmSiv,
+            Self::Aes256GcmSiv(_) => CipherKind::Aes256GcmSiv,
+            Self::ChaCha20Poly1305(_) => CipherKind::ChaCha20Poly1305,
+        }
+    }
+
+    /// Bytes added to the payload by encryption: the nonce and the authentication tag
+    pub fn overhead() -> usize {
+        NONCE_LEN + TAG_LEN
+    }
+
+    pub fn generate_nonce() -> [u8; NONCE_LEN] {
+        Aes128GcmSiv::generate_nonce(&mut OsRng).into()
+    }
+
+    /// Encrypts the data, prepending the nonce to the ciphertext.
+    ///
+    /// The associated data is authenticated but not transmitted.
+    pub fn encrypt(&self, nonce: &[u8; NONCE_LEN], data: &[u8], aad: &[u8]) -> Vec<u8> {
+        let payload = Payload { msg: data, aad };
+        let encrypted = match self {
+            Self::Aes128GcmSiv(cipher) => seal(&**cipher, nonce, payload),
+            Self::Aes256GcmSiv(cipher) => seal(&**cipher, nonce, payload),
+            Self::ChaCha20Poly1305(cipher) => seal(cipher, nonce, payload),
+        }
+        .expect("Failed to encrypt message");
+        [nonce.to_vec(), encrypted].concat()
+    }
+
+    /// Decrypts data laid out as produced by [`Cipher::encrypt`], failing like a wrong key on
+    /// data too short to hold the nonce
+    pub fn decrypt(&self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>, aes_gcm_siv::Error> {
+        let Some((nonce, encrypted)) = data.split_at_checked(NONCE_LEN) else {
+            return Err(aes_gcm_siv::Error);
+        };
+        let payload = Payload {
+            msg: encrypted,
+            aad,
+        };
+        match self {
+            Self::Aes128GcmSiv(cipher) => open(&**cipher, nonce, payload),
+            Self::Aes256GcmSiv(cipher) => open(&**cipher, nonce, payload),
+            Self::ChaCha20Poly1305(cipher) => open(cipher, nonce, payload),
+        }
+    }
+}
+
+fn seal<C: Aead>(cipher: &C, nonce: &[u8], payload: Payload) -> aes_gcm_siv::aead::Result<Vec<u8>> {
+    cipher.encrypt(Nonce::<C>::from_slice(nonce), payload)
+}
+
+fn open<C: Aead>(cipher: &C, nonce: &[u8], payload: Payload) -> aes_gcm_siv::aead::Result<Vec<u8>> {
+    cipher.decrypt(Nonce::<C>::from_slice(nonce), payload)
+}
@@ -1,10 +1,34 @@
-use std::f32;
+use std::{
+    borrow::Cow,
+    f32, f64,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
-use bitvec::{order::Msb0, view::BitView};
+#[cfg(feature = "crypto")]
+use aes_gcm_siv::Aes128GcmSiv;
+use bitvec::{order::Msb0, vec::BitVec, view::BitView};
 use itertools::Itertools;
-use rustfft::{FftPlanner, num_complex::Complex};
+use rustfft::{FftNum, num_complex::Complex, num_traits::Float};
 
-use crate::{DF, F0};
+#[cfg(feature = "crypto")]
+use crate::crypto::{Cipher, CipherKind, NONCE_LEN};
+use crate::{
+    DEFAULT_BASE_FREQ, DEFAULT_DELTA_FREQ,
+    agc::{Agc, AgcState},
+    band::Band,
+    error::{ConfigError, DecodeError},
+    fec::{self, ErrorCorrection},
+    fft::{FftPlans, Planned},
+    filter,
+    morse::{self, MorsePrefix},
+    peak::{PeakDetector, ThresholdDetector},
+    report::{DecodeReport, DecodeStats, FrameMetrics, LOW_CONFIDENCE_SNR_DB},
+    scale::Scale,
+};
 
 type Chunk = u8;
 type Frequency = f32;
@@ -15,37 +39,273 @@ type Frame = Vec<Chunk>;
 /// A vector of samples representing an encoded frame.
 type RawFrame = Vec<Sample>;
 
+/// Default RMS below which a frame is considered silent
+const SILENCE_RMS: f32 = 1e-3;
+/// Default normalized magnitude above which a peak is a tone
+const PEAK_THRESHOLD: f32 = 0.4;
+/// Number of training frames sent by [`Dosr::encode_with_training`]
+const TRAINING_FRAMES: usize = 2;
+/// Seed of the pseudorandom phases of the training tones
+const TRAINING_SEED: u32 = 0x2545_f491;
+/// Received level of a training tone below which its gain stops growing
+const MIN_TRAINING_LEVEL: f32 = 1e-3;
+/// First byte of the payload header
+const HEADER_MAGIC: u8 = 0xd5;
+/// Version of the payload format, in the upper half of the second header byte
+const HEADER_VERSION: u8 = 1;
+/// Protections of the payload named in the lower half of the second header byte
+const PLAINTEXT: u8 = 0;
+const AES_128_GCM_SIV: u8 = 1;
+const AES_256_GCM_SIV: u8 = 2;
+const CHACHA20_POLY1305: u8 = 3;
+/// Length of the big-endian length prefix of the payload (bytes)
+const LENGTH_PREFIX_LEN: usize = 4;
+/// CRC appended to the payload
+const CRC32: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+/// Length of the CRC (bytes)
+const CRC_LEN: usize = 4;
+/// Pole of the DC blocker, whose cutoff sits around 40 Hz at 48 kHz
+const DC_BLOCK_POLE: f32 = 0.995;
+/// Room left by the bandpass filter on either side of the band, in tone spacings
+const BANDPASS_MARGIN: f32 = 2.0;
+/// Largest deviation of the sample clock from nominal that clock tracking follows
+const MAX_CLOCK_DRIFT: f64 = 0.01;
+/// Weight of every new frame length measurement in the tracked clock rate
+const CLOCK_SMOOTHING: f64 = 0.1;
+
+/// Line code applied to the sequence of frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineCoding {
+    /// Frames are transmitted as-is
+    #[default]
+    None,
+    /// Every frame is followed by its complement, guaranteeing a tone change
+    /// on every symbol at the cost of half the throughput
+    Manchester,
+}
+
+/// Order in which the bits of every byte are packed into the chunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Most significant bit first
+    #[default]
+    Msb0,
+    /// Least significant bit first
+    Lsb0,
+}
+
+impl BitOrder {
+    fn other(self) -> Self {
+        match self {
+            BitOrder::Msb0 => BitOrder::Lsb0,
+            BitOrder::Lsb0 => BitOrder::Msb0,
+        }
+    }
+
+    /// Reorders the bits of the byte between this order and most significant bit first
+    fn arrange(self, byte: u8) -> u8 {
+        match self {
+            BitOrder::Msb0 => byte,
+            BitOrder::Lsb0 => byte.reverse_bits(),
+        }
+    }
+}
+
+/// How the tones of every frame are detected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DetectionMethod {
+    /// Peaks of the full FFT spectrum of the frame
+    #[default]
+    Fft,
+    /// Goertzel filters evaluated at the frequencies of the tone grid alone. Detects the tones
+    /// regardless of the configured [`PeakDetector`], and the other frequencies go unnoticed.
+    Goertzel,
+}
+
+/// Floating point precision of the spectral analysis
+///
+/// Single precision FFTs accumulate enough rounding error to tip the comparison between two
+/// neighbouring bins when a tone sits close to the edge between them, double precision resolves
+/// such tones consistently at roughly half the speed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// `f32` FFTs
+    #[default]
+    Single,
+    /// `f64` FFTs
+    Double,
+}
+
 #[derive(Debug)]
 pub struct Dosr {
     /// Base frequency (Hz)
     base_freq: f32,
     /// Frequency delta (Hz)
     delta_freq: f32,
-    bits_per_chunk: usize,
+    /// Musical scale the tones follow instead of the uniform grid, if any
+    scale: Option<Scale>,
+    /// Number of tones each chunk chooses from
     values_per_chunk: usize,
+    /// Whether the values of the chunks pick their tones in Gray code order
+    gray_coding: bool,
     /// Number of chunks transmitted simultaneously
     chunks_per_frame: usize,
     /// Sample rate (Hz)
     sample_rate: f32,
     /// Duration of each audio frame (seconds)
     duration_s: f32,
+    line_coding: LineCoding,
+    /// Order of the bits of every byte in the chunks
+    bit_order: BitOrder,
+    /// Whether decoding retries with the other bit order when the payload fails its checks
+    bit_order_detection: bool,
+    precision: Precision,
+    detection_method: DetectionMethod,
+    /// Amplitude of each tone
+    amplitude: f32,
+    /// Number of amplitude levels each tone can take, carrying extra bits
+    amplitude_levels: usize,
+    /// Whether to scale each frame up to full scale
+    normalize: bool,
+    /// Whether every tone slot continues from the phase the previous frame left it at
+    continuous_phase: bool,
+    /// Fraction of every frame tapered by its raised-cosine envelope, half at either end
+    envelope_rolloff: f32,
+    /// Gain control applied to each frame before detection
+    agc: Option<Agc>,
+    /// Number of frames overlapping at any point of the signal
+    overlap: usize,
+    /// Silence between consecutive frames (s)
+    guard_interval_s: f32,
+    /// Number of samples between the starts of consecutive received frames, if not the hop
+    decode_hop: Option<usize>,
+    /// Whether a chirp the decoder aligns the frames on is sent ahead of them
+    preamble: bool,
+    /// Number of single-tone frames sent ahead of the payload
+    ramp_up: usize,
+    /// Number of frame offsets tried to line the frames up with the symbols
+    timing_steps: usize,
+    /// Coefficient of the pre-emphasis filter applied to the whole signal
+    preemphasis: Option<f32>,
+    /// Whether a constant offset is filtered out of the received signal
+    dc_block: bool,
+    /// Whether the received signal is filtered down to the band of the tones
+    bandpass: bool,
+    /// Normalized magnitude above which a peak is a tone, outside the bands with their own
+    peak_threshold: f32,
+    /// Detection threshold of each chunk's frequency band
+    band_thresholds: Vec<f32>,
+    /// Whether to drop the peaks explained as harmonics of a stronger lower tone
+    harmonic_suppression: bool,
+    /// Strategy finding the tones in the spectrum of every frame
+    peak_detector: Box<dyn PeakDetector>,
+    /// Number of overlapping sub-windows whose spectra are averaged for every frame
+    welch_segments: usize,
+    /// Margin (Hz) above the highest tone past which the spectrum is left unscanned, if limited
+    scan_margin: Option<f32>,
+    /// Frequency and amplitude of the pilot tone sent in every frame
+    pilot: Option<(Frequency, f32)>,
+    /// Whether the frames follow the drift of the recording's sample clock using the pilot
+    clock_tracking: bool,
+    /// Whether the payload starts with a header naming its protection
+    header: bool,
+    /// Whether the payload is preceded by its length
+    length_prefix: bool,
+    /// Whether the payload is followed by its CRC32
+    crc: bool,
+    /// Code protecting the whole payload against flipped bits
+    error_correction: ErrorCorrection,
+    /// Whether the last chunk of every frame is a checksum of its other chunks
+    per_frame_parity: bool,
+    /// Callsign keyed in Morse code ahead of the transmission
+    morse_prefix: Option<MorsePrefix>,
+    /// RMS below which a frame is silent and carries no tones
+    silence_rms: f32,
+    /// Cipher used to encrypt the payload, if any
+    #[cfg(feature = "crypto")]
+    cipher: Option<Cipher>,
+    /// Associated data authenticated by the cipher but not transmitted
+    #[cfg(feature = "crypto")]
+    aad: Vec<u8>,
+    /// FFTs of the frame length, planned once for all the frames
+    fft: FftPlans,
+    /// Chunk band of every bin of the last spectrum layout the band thresholds were applied to
+    bin_bands: Mutex<Option<(BinBandsKey, BinBands)>>,
+}
+
+/// Chunk band of every bin of a spectrum, if any
+type BinBands = Arc<[Option<usize>]>;
+
+/// Everything the chunk bands of the bins of a spectrum depend on
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BinBandsKey {
+    bins: usize,
+    bin_width: f32,
+    base_freq: f32,
+    delta_freq: f32,
+    scale: Option<Scale>,
+    values_per_chunk: usize,
+    chunks_per_frame: usize,
 }
 
 impl Default for Dosr {
     fn default() -> Self {
         Self {
-            base_freq: F0,
-            delta_freq: DF,
+            base_freq: DEFAULT_BASE_FREQ,
+            delta_freq: DEFAULT_DELTA_FREQ,
+            scale: None,
             chunks_per_frame: 6,
-            bits_per_chunk: 4,
             values_per_chunk: 16,
             duration_s: 0.1,
             sample_rate: 48000.0,
+            line_coding: LineCoding::None,
+            gray_coding: false,
+            bit_order: BitOrder::Msb0,
+            bit_order_detection: false,
+            precision: Precision::Single,
+            detection_method: DetectionMethod::Fft,
+            amplitude: 0.5,
+            amplitude_levels: 1,
+            normalize: false,
+            continuous_phase: false,
+            envelope_rolloff: 0.0,
+            agc: None,
+            overlap: 1,
+            guard_interval_s: 0.0,
+            decode_hop: None,
+            preamble: false,
+            ramp_up: 0,
+            timing_steps: 1,
+            preemphasis: None,
+            dc_block: false,
+            bandpass: false,
+            peak_threshold: PEAK_THRESHOLD,
+            band_thresholds: vec![],
+            harmonic_suppression: false,
+            peak_detector: Box::new(ThresholdDetector),
+            welch_segments: 1,
+            scan_margin: None,
+            pilot: None,
+            clock_tracking: false,
+            header: false,
+            length_prefix: false,
+            crc: false,
+            error_correction: ErrorCorrection::None,
+            per_frame_parity: false,
+            morse_prefix: None,
+            silence_rms: SILENCE_RMS,
+            #[cfg(feature = "crypto")]
+            cipher: None,
+            #[cfg(feature = "crypto")]
+            aad: vec![],
+            fft: FftPlans::default(),
+            bin_bands: Mutex::default(),
         }
     }
 }
 
 impl Dosr {
+    /// Panics if the configuration is invalid, see [`Dosr::validate`]
     pub fn new(
         base_freq: f32,
         delta_freq: f32,
@@ -54,14 +314,204 @@ impl Dosr {
         duration_s: f32,
         sample_rate: f32,
     ) -> Self {
-        Self {
+        Self::try_new(
             base_freq,
             delta_freq,
-            chunks_per_frame,
             bits_per_chunk,
+            chunks_per_frame,
+            duration_s,
+            sample_rate,
+        )
+        .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like [`Dosr::new`], returning an error instead of panicking on an invalid configuration
+    pub fn try_new(
+        base_freq: f32,
+        delta_freq: f32,
+        bits_per_chunk: usize,
+        chunks_per_frame: usize,
+        duration_s: f32,
+        sample_rate: f32,
+    ) -> Result<Self, ConfigError> {
+        if !(1..=Chunk::BITS as usize).contains(&bits_per_chunk) {
+            return Err(ConfigError::BitsPerChunk(bits_per_chunk));
+        }
+        let dosr = Self {
+            base_freq,
+            delta_freq,
+            chunks_per_frame,
             values_per_chunk: 2usize.pow(bits_per_chunk as u32),
             duration_s,
             sample_rate,
+            ..Default::default()
+        };
+        dosr.validate()?;
+        Ok(dosr)
+    }
+
+    /// Checks that the frames hold samples and are long enough to tell neighbouring tones apart.
+    ///
+    /// The builders don't validate the configuration, so call this after changing the duration
+    /// or sample rate.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !self.sample_rate.is_finite() || self.sample_rate <= 0.0 {
+            return Err(ConfigError::SampleRate(self.sample_rate));
+        }
+        if !self.duration_s.is_finite() || self.duration_s <= 0.0 {
+            return Err(ConfigError::Duration(self.duration_s));
+        }
+        if self.chunks_per_frame == 0 {
+            return Err(ConfigError::NoChunks);
+        }
+        if self.samples_per_frame() == 0 {
+            return Err(ConfigError::EmptyFrame {
+                duration_s: self.duration_s,
+                sample_rate: self.sample_rate,
+            });
+        }
+        // Tones are only told apart when they fall in different bins
+        let resolution = self.sample_rate / self.samples_per_frame() as f32;
+        let spacing = self.tone_spacing();
+        if spacing < resolution {
+            return Err(ConfigError::Unresolvable {
+                delta_freq: spacing,
+                duration_s: self.duration_s,
+                resolution,
+            });
+        }
+        let highest = self.tone_frequency(self.tones().saturating_sub(1));
+        let nyquist = self.sample_rate / 2.0;
+        if highest >= nyquist {
+            return Err(ConfigError::AboveNyquist {
+                frequency: highest,
+                nyquist,
+            });
+        }
+        Ok(())
+    }
+
+    /// Compares the settings both ends of a transmission must share, listing every mismatch
+    /// between this configuration and the other one
+    pub fn validate_against(&self, other: &Dosr) -> Result<(), Vec<String>> {
+        let mut mismatches = vec![];
+        let mut compare = |name: &str, ours: String, theirs: String| {
+            if ours != theirs {
+                mismatches.push(format!("{name} is {ours} but the other expects {theirs}"));
+            }
+        };
+        compare(
+            "base_freq",
+            self.base_freq.to_string(),
+            other.base_freq.to_string(),
+        );
+        compare(
+            "delta_freq",
+            self.delta_freq.to_string(),
+            other.delta_freq.to_string(),
+        );
+        compare(
+            "scale",
+            format!("{:?}", self.scale),
+            format!("{:?}", other.scale),
+        );
+        compare(
+            "values_per_chunk",
+            self.values_per_chunk.to_string(),
+            other.values_per_chunk.to_string(),
+        );
+        compare(
+            "chunks_per_frame",
+            self.chunks_per_frame.to_string(),
+            other.chunks_per_frame.to_string(),
+        );
+        compare(
+            "sample_rate",
+            self.sample_rate.to_string(),
+            other.sample_rate.to_string(),
+        );
+        compare(
+            "duration_s",
+            self.duration_s.to_string(),
+            other.duration_s.to_string(),
+        );
+        compare(
+            "gray_coding",
+            self.gray_coding.to_string(),
+            other.gray_coding.to_string(),
+        );
+        compare(
+            "line_coding",
+            format!("{:?}", self.line_coding),
+            format!("{:?}", other.line_coding),
+        );
+        compare(
+            "bit_order",
+            format!("{:?}", self.bit_order),
+            format!("{:?}", other.bit_order),
+        );
+        compare(
+            "per_frame_parity",
+            self.per_frame_parity.to_string(),
+            other.per_frame_parity.to_string(),
+        );
+        compare(
+            "amplitude_levels",
+            self.amplitude_levels.to_string(),
+            other.amplitude_levels.to_string(),
+        );
+        compare(
+            "overlap",
+            self.overlap.to_string(),
+            other.overlap.to_string(),
+        );
+        compare(
+            "guard_interval_s",
+            self.guard_interval_s.to_string(),
+            other.guard_interval_s.to_string(),
+        );
+        compare(
+            "preamble",
+            self.preamble.to_string(),
+            other.preamble.to_string(),
+        );
+        compare(
+            "ramp_up",
+            self.ramp_up.to_string(),
+            other.ramp_up.to_string(),
+        );
+        compare(
+            "pilot",
+            format!("{:?}", self.pilot.map(|(frequency, _)| frequency)),
+            format!("{:?}", other.pilot.map(|(frequency, _)| frequency)),
+        );
+        compare("header", self.header.to_string(), other.header.to_string());
+        compare("crc", self.crc.to_string(), other.crc.to_string());
+        compare(
+            "error_correction",
+            format!("{:?}", self.error_correction),
+            format!("{:?}", other.error_correction),
+        );
+        compare(
+            "length_prefix",
+            self.length_prefix.to_string(),
+            other.length_prefix.to_string(),
+        );
+        #[cfg(feature = "crypto")]
+        compare(
+            "cipher",
+            format!("{:?}", self.cipher.as_ref().map(Cipher::kind)),
+            format!("{:?}", other.cipher.as_ref().map(Cipher::kind)),
+        );
+        #[cfg(not(feature = "crypto"))]
+        compare(
+            "encryption",
+            self.has_cipher().to_string(),
+            other.has_cipher().to_string(),
+        );
+        match mismatches.is_empty() {
+            true => Ok(()),
+            false => Err(mismatches),
         }
     }
 
@@ -77,151 +527,2401 @@ impl Dosr {
 
     pub fn with_duration_s(mut self, duration_s: f32) -> Self {
         self.duration_s = duration_s;
+        self.fft = FftPlans::default();
         self
     }
 
     pub fn with_sample_rate(mut self, sample_rate: f32) -> Self {
         self.sample_rate = sample_rate;
+        self.fft = FftPlans::default();
         self
     }
 
-    pub fn sample_rate(&self) -> f32 {
-        self.sample_rate
+    /// Places the tones on the notes of a musical scale rising from the base frequency, so the
+    /// transmission sounds like a melody rather than a warble. Decoding maps every tone to the
+    /// nearest note.
+    ///
+    /// A scale packs only a handful of notes per octave, so it suits grids of a few dozen tones,
+    /// and its lowest notes must still be resolvable, see [`Dosr::validate`].
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = Some(scale);
+        self
     }
-}
 
-/// Encoding functionality
-impl Dosr {
-    pub fn calculate_frequency(&self, data: u8, chunk_index: usize) -> f32 {
-        assert!(data < self.values_per_chunk as u8, "Value exceeds maximum");
+    /// Fits the tone grid into the given frequency band
+    pub fn with_band(mut self, band: Band) -> Self {
+        self.base_freq = band.base_freq();
+        self.delta_freq = band.delta_freq();
+        self.chunks_per_frame = band.chunks_per_frame();
+        self
+    }
+
+    /// Packs the bits of every byte in the given order, most significant first by default. Only
+    /// applies when the chunks carry whole bits.
+    pub fn with_bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.bit_order = bit_order;
+        self
+    }
+
+    /// Reserves the last chunk of every frame for a checksum of its other chunks, so decoding
+    /// can tell exactly which frames are corrupt, see
+    /// [`DecodeReport::corrupt_frames`](crate::DecodeReport::corrupt_frames). Broadcasts keep
+    /// their own framing and carry no checksum.
+    pub fn with_per_frame_parity(mut self, parity: bool) -> Self {
         assert!(
-            chunk_index < self.chunks_per_frame,
-            "Chunk index out of bounds"
+            !parity || self.chunks_per_frame >= 2,
+            "Per-frame parity needs a chunk for data besides the checksum"
         );
-        self.base_freq
-            + (data + (self.values_per_chunk * chunk_index) as u8) as f32 * self.delta_freq
+        self.per_frame_parity = parity;
+        self
     }
 
-    /// Generates samples for a sine wave with the specified arguments
-    fn generate_sine_wave(&self, frequency: f32, amplitude: f32) -> Vec<f32> {
-        let num_samples = (self.duration_s * self.sample_rate) as u32;
-        (0..num_samples)
-            .map(|n| {
-                let time = n as f32 / self.sample_rate;
-                amplitude * (2.0 * f32::consts::PI * frequency * time).sin()
-            })
-            .collect()
+    /// Retries decoding with the other bit order when the payload fails its checks, reporting
+    /// the order that worked in [`DecodeReport::bit_order`](crate::DecodeReport::bit_order).
+    ///
+    /// A wrong bit order is only noticed with a header or a cipher, which it garbles.
+    pub fn with_bit_order_detection(mut self, detect: bool) -> Self {
+        self.bit_order_detection = detect;
+        self
     }
 
-    fn bytes_to_chunks(&self, data: &[u8]) -> Vec<Chunk> {
-        let bit_view = data.view_bits::<Msb0>();
-        bit_view
-            .chunks(self.bits_per_chunk)
-            .map(|c| {
-                c.iter()
-                    .fold(0u8, |acc, bit| (acc << 1) | if *bit { 1 } else { 0 })
-            })
-            .collect_vec()
+    pub fn with_line_coding(mut self, line_coding: LineCoding) -> Self {
+        self.line_coding = line_coding;
+        self
     }
 
-    fn chunks_to_frames(&self, chunks: &[Chunk]) -> Vec<Frame> {
-        chunks
-            .chunks(self.chunks_per_frame)
-            .map(|chunk| chunk.to_vec())
-            .collect_vec()
+    /// Sets the precision of the FFTs used for detection
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
     }
 
-    fn encode_frame(&self, frame: Frame) -> RawFrame {
-        let num_samples = (self.duration_s * self.sample_rate) as usize;
-        let mut samples = vec![0.0; num_samples];
-        frame
-            .into_iter()
-            .enumerate()
-            .map(|(chunk_idx, v)| self.calculate_frequency(v, chunk_idx))
-            .map(|f| self.generate_sine_wave(f, 0.5))
-            .for_each(|w| {
-                for i in 0..num_samples {
-                    samples[i] += w[i];
-                }
-            });
-        samples
+    /// Sets how the tones of every frame are detected when decoding
+    pub fn with_detection_method(mut self, method: DetectionMethod) -> Self {
+        self.detection_method = method;
+        self
     }
 
-    pub fn encode_data(&self, data: &[u8]) -> Vec<f32> {
-        let chunks = self.bytes_to_chunks(data);
-        let frames = self.chunks_to_frames(&chunks);
-        frames
-            .into_iter()
-            .flat_map(|frame| self.encode_frame(frame))
-            .collect_vec()
+    /// Sets the amplitude of each tone.
+    ///
+    /// Frames sum `chunks_per_frame` tones, which may add up beyond full scale above
+    /// `1 / chunks_per_frame`. The whole signal is then scaled down until its peak is at full
+    /// scale, so it never clips, and the tones are quieter than the amplitude set.
+    pub fn with_amplitude(mut self, amplitude: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&amplitude),
+            "Amplitude must be between 0 and 1"
+        );
+        self.amplitude = amplitude;
+        self
     }
-}
 
-/// Decoding functionality
-impl Dosr {
-    fn split_into_frames(&self, samples: &[f32]) -> impl Iterator<Item = RawFrame> {
-        let samples_per_frame = (self.sample_rate * self.duration_s) as usize;
-        samples
-            .chunks(samples_per_frame)
-            .map(|chunk| chunk.to_vec())
+    /// Layers amplitude-shift keying on the tones, each carrying `log2(levels)` extra bits in
+    /// its amplitude, quantized into `levels` evenly spaced levels up to the tone amplitude.
+    ///
+    /// The decoder measures the levels against the strongest tone of the whole signal, so they
+    /// don't survive channels with a fluctuating gain, normalization nor AGC.
+    pub fn with_amplitude_levels(mut self, levels: usize) -> Self {
+        assert!(
+            levels.is_power_of_two(),
+            "The number of amplitude levels must be a power of two"
+        );
+        assert!(
+            self.values_per_chunk * levels <= 1 << Chunk::BITS,
+            "Each chunk can carry at most {} symbols",
+            1 << Chunk::BITS
+        );
+        self.amplitude_levels = levels;
+        self
     }
 
-    fn perform_fft(&self, encoded_frame: &[f32]) -> Vec<Complex<f32>> {
-        let mut planner = FftPlanner::<f32>::new();
-        let fft = planner.plan_fft_forward(encoded_frame.len());
-        let mut buffer = encoded_frame
-            .iter()
-            .map(|s| Complex::new(*s, 0.0))
-            .collect_vec();
-        fft.process(&mut buffer);
-        buffer
+    /// Sets the number of tones each chunk chooses from, which doesn't need to be a power of
+    /// two, e.g. 10 tones for decimal digits.
+    ///
+    /// When the symbols a chunk can carry (tones times amplitude levels) aren't a power of two,
+    /// the data is packed in base `n` instead of bits: every byte is written as the fewest
+    /// base `n` digits that can hold it, most significant first, one digit per chunk.
+    pub fn with_values_per_chunk(mut self, values: usize) -> Self {
+        assert!(values >= 2, "Each chunk needs at least 2 values");
+        assert!(
+            values * self.amplitude_levels <= 1 << Chunk::BITS,
+            "Each chunk can carry at most {} symbols",
+            1 << Chunk::BITS
+        );
+        self.values_per_chunk = values;
+        self
     }
 
-    fn normalize_fft(&self, fft_output: &[Complex<f32>]) -> Vec<f32> {
-        let magnitudes = fft_output
-            .iter()
-            .take(fft_output.len() / 2)
-            .map(|c| c.norm())
-            .collect_vec();
-        let max_magnitude = magnitudes.iter().cloned().fold(0.0f32, f32::max);
-        magnitudes.iter().map(|m| m / max_magnitude).collect_vec()
+    /// Sets the number of bits each chunk carries, choosing between `2^bits` tones
+    pub fn with_bits_per_chunk(self, bits: usize) -> Self {
+        assert!(
+            (1..=Chunk::BITS as usize).contains(&bits),
+            "Each chunk carries between 1 and {} bits",
+            Chunk::BITS
+        );
+        self.with_values_per_chunk(1 << bits)
     }
 
-    fn detect_frequencies(&self, samples: &[f32]) -> Vec<Frequency> {
-        let fft_output = self.perform_fft(samples);
-        let magnitudes = self.normalize_fft(&fft_output);
-        let bin_width = self.sample_rate / fft_output.len() as f32;
-        let mut frequencies = vec![];
-        for i in 0..magnitudes.len() {
-            let mag = magnitudes[i];
-            if mag > 0.4 && mag > magnitudes[i - 1] && mag > magnitudes[i + 1] {
-                frequencies.push(i as f32 * bin_width);
-            }
-        }
-        frequencies
+    /// Sets the number of chunks, and so of simultaneous tones, of every frame
+    pub fn with_chunks_per_frame(mut self, chunks: usize) -> Self {
+        assert!(chunks >= 1, "Each frame needs at least a chunk");
+        assert!(
+            !self.per_frame_parity || chunks >= 2,
+            "Per-frame parity needs a chunk for data besides the checksum"
+        );
+        assert!(
+            self.ramp_up == 0 || chunks > 1,
+            "A ramp-up requires more than one chunk per frame"
+        );
+        self.chunks_per_frame = chunks;
+        self
     }
 
-    fn decode_frequency(&self, freq: f32, chunk_index: usize) -> u8 {
-        let value = ((freq - self.base_freq) / self.delta_freq).round() as usize;
-        let value = value - self.values_per_chunk * chunk_index;
-        value as u8
+    /// Assigns the values of every chunk to its tones in Gray code order, so a tone detected a
+    /// tone spacing off flips a single bit of the chunk instead of up to all of them. Only
+    /// applies when the tones per chunk are a power of two.
+    pub fn with_gray_coding(mut self, gray_coding: bool) -> Self {
+        self.gray_coding = gray_coding;
+        self
     }
 
-    /// Decodes a vector of frequencies into a frame.
-    fn decode_frame(&self, samples: &RawFrame) -> Frame {
-        self.detect_frequencies(samples)
-            .into_iter()
-            .enumerate()
-            .map(|(chunk_idx, f)| self.decode_frequency(f, chunk_idx))
-            .collect_vec()
+    /// Scales every encoded frame so that its peak reaches full scale
+    pub fn with_normalization(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
     }
 
-    pub fn decode(&self, samples: &[f32]) -> Vec<u8> {
-        self.split_into_frames(samples)
-            .flat_map(|frame| self.decode_frame(&frame))
-            .chunks(8 / self.bits_per_chunk)
-            .into_iter()
-            .map(|c| c.fold(0u8, |acc, x| (acc << self.bits_per_chunk) | (x)))
-            .collect_vec()
+    /// Continues the tone of every chunk slot from the phase the previous frame left it at,
+    /// instead of restarting every tone at phase zero, so the signal doesn't jump at the frame
+    /// boundaries. The jumps click and splatter energy across the spectrum.
+    ///
+    /// The pilot still restarts every frame, since the decoder times the frames on its phase,
+    /// and normalization scales every frame apart.
+    pub fn with_continuous_phase(mut self, continuous: bool) -> Self {
+        self.continuous_phase = continuous;
+        self
+    }
+
+    /// Fades every frame in and out with a raised-cosine (Tukey) envelope, tapering `rolloff`
+    /// of the frame, half at either end. Tones starting and stopping abruptly splatter energy
+    /// over the neighbouring tones, 0 (the default) keeps the frames hard-edged and 1 tapers
+    /// them in full, like a Hann window.
+    pub fn with_envelope_rolloff(mut self, rolloff: f32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&rolloff),
+            "The envelope roll-off must be between 0 and 1"
+        );
+        self.envelope_rolloff = rolloff;
+        self
+    }
+
+    pub fn with_agc(mut self, agc: Agc) -> Self {
+        self.agc = Some(agc);
+        self
+    }
+
+    /// Encrypts the payload with the given cipher before modulating it
+    #[cfg(feature = "crypto")]
+    pub fn with_cipher(mut self, cipher: Aes128GcmSiv) -> Self {
+        self.cipher = Some(cipher.into());
+        self
+    }
+
+    /// Encrypts the payload with a cipher of the given kind before modulating it, keyed with
+    /// the key, which must be [`CipherKind::key_len`] bytes long
+    #[cfg(feature = "crypto")]
+    pub fn with_key(mut self, kind: CipherKind, key: &[u8]) -> Self {
+        let cipher = Cipher::new(kind, key);
+        assert!(
+            cipher.is_some(),
+            "The keys of {kind:?} are {} bytes long",
+            kind.key_len()
+        );
+        self.cipher = cipher;
+        self
+    }
+
+    /// Binds the payload to associated data, which the receiver must know to decrypt it.
+    ///
+    /// Only used when a cipher is set.
+    #[cfg(feature = "crypto")]
+    pub fn with_aad(mut self, aad: &[u8]) -> Self {
+        self.aad = aad.to_vec();
+        self
+    }
+
+    /// Overlaps consecutive frames, advancing only `1 / overlap` of a frame between them.
+    ///
+    /// Frames are shaped with a Hann window and overlap-added, multiplying the symbol rate by
+    /// `overlap`. Detection gets less reliable as more frames overlap, 2 is the sensible maximum.
+    pub fn with_overlap(mut self, overlap: usize) -> Self {
+        self.overlap = overlap.max(1);
+        self
+    }
+
+    /// Leaves a gap of silence between consecutive frames, none by default. The decoder skips
+    /// it, so echoes of a frame die out before the next one instead of smearing into it.
+    ///
+    /// The gap should stay well below a frame, since a frame of silence ends a transmission.
+    pub fn with_guard_interval_s(mut self, guard_interval_s: f32) -> Self {
+        assert!(
+            guard_interval_s.is_finite() && guard_interval_s >= 0.0,
+            "The guard interval must be a non-negative duration"
+        );
+        self.guard_interval_s = guard_interval_s;
+        self
+    }
+
+    /// Splits the received signal into frames starting `samples` apart instead of a hop apart,
+    /// to inspect how detection changes around the symbol boundaries. Payloads only decode
+    /// correctly at the hop they were encoded with.
+    pub fn with_decode_hop(mut self, samples: usize) -> Self {
+        assert!(samples > 0, "The decode hop must be at least a sample");
+        self.decode_hop = Some(samples);
+        self
+    }
+
+    /// Number of samples between the starts of consecutive received frames
+    pub fn decode_hop(&self) -> usize {
+        self.decode_hop.unwrap_or_else(|| self.hop())
+    }
+
+    /// Sends a frame-long chirp across the tone grid ahead of the frames, which the decoder
+    /// locates by cross-correlation to line the frames up with the symbols however much
+    /// silence or noise precedes the transmission, see [`Dosr::find_frame_start`]. Without it,
+    /// the decoder assumes the first frame starts at the first sample, or searches a few offsets
+    /// within a frame with [`Dosr::with_timing_search`].
+    pub fn with_preamble(mut self, preamble: bool) -> Self {
+        self.preamble = preamble;
+        self
+    }
+
+    /// Precedes the payload with `frames` single-tone frames, alternating between both ends of
+    /// the tone grid, which are easy to acquire on marginal links before the full density
+    /// payload starts.
+    ///
+    /// The decoder skips the leading frames with fewer tones than `chunks_per_frame`, up to
+    /// `frames` of them, so it needs more than one chunk per frame to tell them apart.
+    pub fn with_ramp_up(mut self, frames: usize) -> Self {
+        assert!(
+            frames == 0 || self.chunks_per_frame > 1,
+            "A ramp-up requires more than one chunk per frame"
+        );
+        self.ramp_up = frames;
+        self
+    }
+
+    /// Makes decoding try `steps` offsets evenly spread over a frame, keeping the one at which
+    /// the detected tones are the strongest.
+    ///
+    /// A cheap way to decode clean recordings that start a fraction of a frame off, at the cost
+    /// of decoding the signal `steps` times. 1, the default, disables the search.
+    pub fn with_timing_search(mut self, steps: usize) -> Self {
+        self.timing_steps = steps.max(1);
+        self
+    }
+
+    /// Boosts the high frequencies of the encoded signal with a first-order filter,
+    /// undoing it on decode. Helps on channels that roll off the highs, `alpha` around
+    /// 0.9 is a common choice.
+    pub fn with_preemphasis(mut self, alpha: f32) -> Self {
+        self.preemphasis = Some(alpha);
+        self
+    }
+
+    /// Filters any constant offset out of the received signal before decoding it. Some ADCs
+    /// record with one, and its DC bin would outweigh the tones when normalizing the spectrum.
+    pub fn with_dc_block(mut self, dc_block: bool) -> Self {
+        self.dc_block = dc_block;
+        self
+    }
+
+    /// Filters the received signal down to the band of the tones and the pilot, with a couple
+    /// of tone spacings to spare, before decoding it. Out-of-band noise such as voices or hum
+    /// would otherwise outweigh the tones when normalizing the spectrum.
+    pub fn with_bandpass(mut self, bandpass: bool) -> Self {
+        self.bandpass = bandpass;
+        self
+    }
+
+    /// Sets the normalized magnitude, relative to the strongest bin of the frame, above which a
+    /// peak is taken for a tone, 0.4 by default. Lower it for weak or noisy signals whose tones
+    /// don't stand out as much.
+    pub fn with_peak_threshold(mut self, threshold: f32) -> Self {
+        assert!(
+            (0.0..1.0).contains(&threshold),
+            "The peak threshold must be between 0 and 1"
+        );
+        self.peak_threshold = threshold;
+        self
+    }
+
+    /// Sets a separate detection threshold for the frequency band of each chunk index, to
+    /// compensate for channels that attenuate some bands more than others.
+    ///
+    /// Frequencies outside every band keep the threshold of [`Dosr::with_peak_threshold`].
+    pub fn with_band_thresholds(mut self, thresholds: Vec<f32>) -> Self {
+        assert_eq!(
+            thresholds.len(),
+            self.chunks_per_frame,
+            "There must be a threshold per chunk"
+        );
+        self.band_thresholds = thresholds;
+        self
+    }
+
+    /// Sets the RMS below which a frame is treated as silence and yields no tones, 1e-3 by
+    /// default. Raise it to ignore the hiss of a quiet line between transmissions.
+    pub fn with_silence_threshold(mut self, rms: f32) -> Self {
+        assert!(
+            rms.is_finite() && rms >= 0.0,
+            "The silence threshold must be a non-negative RMS"
+        );
+        self.silence_rms = rms;
+        self
+    }
+
+    /// Drops the surplus peaks of a frame that sit at a multiple of a stronger lower tone, which
+    /// speakers that distort reproduce strongly enough to be mistaken for another chunk's tone
+    pub fn with_harmonic_suppression(mut self, suppress: bool) -> Self {
+        self.harmonic_suppression = suppress;
+        self
+    }
+
+    /// Finds the tones of every frame with the given detector instead of the default
+    /// [`ThresholdDetector`]
+    pub fn with_peak_detector(mut self, detector: Box<dyn PeakDetector>) -> Self {
+        self.peak_detector = detector;
+        self
+    }
+
+    /// Averages the magnitude spectra of `segments` half-overlapping sub-windows of every frame
+    /// before detecting its tones, trading frequency resolution for a steadier noise floor.
+    ///
+    /// Each sub-window spans `2 / (segments + 1)` of the frame, so its resolution must still
+    /// separate adjacent tones.
+    pub fn with_welch_segments(mut self, segments: usize) -> Self {
+        assert!(segments > 0, "There must be at least one segment");
+        self.welch_segments = segments;
+        self
+    }
+
+    /// Limits the spectrum of every frame to `margin` Hz above the highest tone or the pilot,
+    /// skipping the magnitudes of the upper bins nothing is sent on. Their noise no longer sets
+    /// the normalization either, so the thresholds compare against the tones alone.
+    pub fn with_scan_margin(mut self, margin: f32) -> Self {
+        assert!(
+            margin.is_finite() && margin >= 0.0,
+            "The scan margin must be a non-negative frequency"
+        );
+        self.scan_margin = Some(margin);
+        self
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Frequency of the lowest tone of the grid (Hz), [`DEFAULT_BASE_FREQ`] by default
+    pub fn base_freq(&self) -> f32 {
+        self.base_freq
+    }
+
+    /// Spacing of the uniform tone grid (Hz), [`DEFAULT_DELTA_FREQ`] by default
+    pub fn delta_freq(&self) -> f32 {
+        self.delta_freq
+    }
+
+    /// Duration of every frame (seconds)
+    pub fn duration_s(&self) -> f32 {
+        self.duration_s
+    }
+
+    /// Number of tones each chunk chooses from
+    pub fn values_per_chunk(&self) -> usize {
+        self.values_per_chunk
+    }
+
+    /// Adds a pilot tone outside the tone grid to every frame. The decoder follows it from frame
+    /// to frame to correct a frequency offset, even one that drifts across the recording.
+    ///
+    /// The pilot should sit at least a couple of tone spacings away from the grid, or the
+    /// leakage of the nearest tones can be mistaken for it.
+    pub fn with_pilot(mut self, frequency: f32, amplitude: f32) -> Self {
+        assert!(
+            self.is_off_grid(frequency),
+            "The pilot must be at least a tone spacing away from the tone grid"
+        );
+        assert!(
+            (0.0..=1.0).contains(&amplitude),
+            "Amplitude must be between 0 and 1"
+        );
+        self.pilot = Some((frequency, amplitude));
+        self
+    }
+
+    /// Follows the drift of the sample clock of long recordings, whose frame boundaries slowly
+    /// slip on cheap sound cards. Every frame is realigned on the phase of the pilot tone, which
+    /// restarts with every frame, and the effective frame length is re-estimated as it goes.
+    ///
+    /// Only takes effect with a pilot, see [`Dosr::with_pilot`]. The sample clock may drift by
+    /// less than a pilot period per frame.
+    pub fn with_clock_tracking(mut self, track: bool) -> Self {
+        self.clock_tracking = track;
+        self
+    }
+
+    /// Prepends the length of the payload as 4 big-endian bytes, ahead of the header if any, so the
+    /// decoder drops whatever it demodulates past the end of the message, such as the bytes of
+    /// noise following the transmission. Both ends must agree on using it.
+    pub fn with_length_prefix(mut self, length_prefix: bool) -> Self {
+        self.length_prefix = length_prefix;
+        self
+    }
+
+    /// Protects the payload against flipped bits, see [`ErrorCorrection`]. The code is applied
+    /// to the bytes, so it works with any number of bits per chunk. Both ends must agree on it.
+    pub fn with_error_correction(mut self, error_correction: ErrorCorrection) -> Self {
+        self.error_correction = error_correction;
+        self
+    }
+
+    /// Code protecting the payload, see [`Dosr::with_error_correction`]
+    pub fn error_correction(&self) -> ErrorCorrection {
+        self.error_correction
+    }
+
+    /// Appends the CRC32 of the payload, header included, and checks it when decoding, which
+    /// fails with [`DecodeError::ChecksumMismatch`] on a corrupt message instead of returning it.
+    /// Both ends must agree on using it.
+    pub fn with_crc(mut self, crc: bool) -> Self {
+        self.crc = crc;
+        self
+    }
+
+    /// Whether the payload is followed by its CRC32, see [`Dosr::with_crc`]
+    pub fn has_crc(&self) -> bool {
+        self.crc
+    }
+
+    /// Prepends a 2-byte header to the payload with the format version and whether it's
+    /// encrypted, so the decoder follows the sender instead of its own cipher setting and
+    /// reports a missing key clearly. Both ends must agree on using it.
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Prepends the callsign in Morse code, keyed at 20 WPM on a 700 Hz carrier, so that a
+    /// listener can identify the transmission. The prefix is padded with silence to whole
+    /// frames, and the decoder skips it.
+    ///
+    /// Panics if Morse code can't spell the callsign or the carrier falls on the tone grid, see
+    /// [`Dosr::try_with_morse_prefix`].
+    pub fn with_morse_prefix(self, callsign: &str) -> Self {
+        self.try_with_morse_prefix(callsign)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like [`Dosr::with_morse_prefix`], returning an error instead of panicking on a callsign
+    /// Morse code can't spell or a carrier on the tone grid
+    pub fn try_with_morse_prefix(mut self, callsign: &str) -> Result<Self, ConfigError> {
+        if !morse::is_encodable(callsign) {
+            return Err(ConfigError::MorseCallsign(callsign.to_string()));
+        }
+        if !self.is_off_grid(morse::DEFAULT_FREQUENCY) {
+            return Err(ConfigError::MorseOnGrid(morse::DEFAULT_FREQUENCY));
+        }
+        self.morse_prefix = Some(MorsePrefix {
+            text: callsign.to_string(),
+            wpm: morse::DEFAULT_WPM,
+            frequency: morse::DEFAULT_FREQUENCY,
+        });
+        Ok(self)
+    }
+
+    /// Sets the speed (words per minute) and carrier frequency of the Morse prefix
+    pub fn with_morse_keying(mut self, wpm: f32, frequency: f32) -> Self {
+        assert!(wpm > 0.0, "The Morse speed must be positive");
+        assert!(
+            self.is_off_grid(frequency),
+            "The Morse carrier must be at least a tone spacing away from the tone grid"
+        );
+        let prefix = self
+            .morse_prefix
+            .as_mut()
+            .expect("Set a Morse prefix before its keying");
+        prefix.wpm = wpm;
+        prefix.frequency = frequency;
+        self
+    }
+
+    /// Whether the frequency is at least a tone spacing away from the tone grid
+    fn is_off_grid(&self, frequency: f32) -> bool {
+        let spacing = self.tone_spacing();
+        frequency <= self.tone_frequency(0) - spacing
+            || frequency >= self.tone_frequency(self.tones() - 1) + spacing
+    }
+
+    /// Number of tones of the grid, over all chunks
+    fn tones(&self) -> usize {
+        self.values_per_chunk * self.chunks_per_frame
+    }
+
+    /// Frequency of the tone at the given index of the grid
+    fn tone_frequency(&self, tone: usize) -> f32 {
+        match self.scale {
+            Some(scale) => scale.frequency(self.base_freq, tone),
+            None => self.base_freq + tone as f32 * self.delta_freq,
+        }
+    }
+
+    /// Smallest distance between neighbouring tones, which on a scale is between its lowest notes
+    fn tone_spacing(&self) -> f32 {
+        match self.scale {
+            Some(_) => (1..self.tones())
+                .map(|tone| self.tone_frequency(tone) - self.tone_frequency(tone - 1))
+                .fold(f32::INFINITY, f32::min),
+            None => self.delta_freq,
+        }
+    }
+
+    /// Index of the tone of the grid closest to the frequency, if it's within half the distance
+    /// to the neighbouring tones
+    fn nearest_tone(&self, freq: f32) -> Option<usize> {
+        if self.scale.is_none() {
+            let tone = ((freq - self.base_freq) / self.delta_freq).round();
+            return (tone >= 0.0 && (tone as usize) < self.tones()).then_some(tone as usize);
+        }
+        let tones = self.tones();
+        let tone = (0..tones).min_by(|a, b| {
+            let distance = |tone: usize| (freq - self.tone_frequency(tone)).abs();
+            distance(*a).total_cmp(&distance(*b))
+        })?;
+        let frequency = self.tone_frequency(tone);
+        let below = tone
+            .checked_sub(1)
+            .map(|t| frequency - self.tone_frequency(t));
+        let above = (tone + 1 < tones).then(|| self.tone_frequency(tone + 1) - frequency);
+        let half_gap = below.into_iter().chain(above).fold(f32::INFINITY, f32::min) / 2.0;
+        ((freq - frequency).abs() < half_gap).then_some(tone)
+    }
+
+    fn has_cipher(&self) -> bool {
+        #[cfg(feature = "crypto")]
+        return self.cipher.is_some();
+        #[cfg(not(feature = "crypto"))]
+        false
+    }
+
+    /// Number of chunks, and so of simultaneous tones, of every frame
+    pub fn chunks_per_frame(&self) -> usize {
+        self.chunks_per_frame
+    }
+
+    pub fn samples_per_frame(&self) -> usize {
+        (self.sample_rate * self.duration_s) as usize
+    }
+
+    /// Number of distinct symbols each chunk can carry, in its frequency and amplitude
+    pub(crate) fn symbols(&self) -> usize {
+        self.values_per_chunk * self.amplitude_levels
+    }
+
+    /// Number of bits carried by each chunk, if the data is packed in bits
+    fn symbol_bits(&self) -> Option<usize> {
+        self.symbols()
+            .is_power_of_two()
+            .then(|| self.symbols().trailing_zeros() as usize)
+    }
+
+    /// Number of base `symbols` digits holding a byte, if the data is packed in digits
+    fn digits_per_byte(&self) -> usize {
+        let mut digits = 1;
+        while self.symbols().pow(digits) < 1 << u8::BITS {
+            digits += 1;
+        }
+        digits as usize
+    }
+
+    /// Number of samples between the starts of consecutive frames, including the guard interval
+    fn hop(&self) -> usize {
+        (self.samples_per_frame() / self.overlap).max(1) + self.guard_samples()
+    }
+
+    /// Number of silent samples between consecutive frames
+    fn guard_samples(&self) -> usize {
+        (self.sample_rate * self.guard_interval_s) as usize
+    }
+
+    /// Hann window spanning a frame
+    fn window(&self) -> Vec<f32> {
+        let n = self.samples_per_frame();
+        (0..n)
+            .map(|i| 0.5 - 0.5 * (2.0 * f32::consts::PI * i as f32 / n as f32).cos())
+            .collect_vec()
+    }
+}
+
+/// Encoding functionality
+impl Dosr {
+    pub fn calculate_frequency(&self, data: u8, chunk_index: usize) -> f32 {
+        assert!(
+            (data as usize) < self.values_per_chunk,
+            "Value exceeds maximum"
+        );
+        assert!(
+            chunk_index < self.chunks_per_frame,
+            "Chunk index out of bounds"
+        );
+        self.tone_frequency(self.value_tone(data as usize) + self.values_per_chunk * chunk_index)
+    }
+
+    /// Position of the tone carrying the value among the tones of its chunk. In Gray code, the
+    /// tone whose Gray code is the value, so neighbouring tones carry values a bit apart.
+    fn value_tone(&self, value: usize) -> usize {
+        if !self.uses_gray_code() {
+            return value;
+        }
+        let (mut tone, mut shifted) = (value, value >> 1);
+        while shifted > 0 {
+            tone ^= shifted;
+            shifted >>= 1;
+        }
+        tone
+    }
+
+    /// Value carried by the tone at the position among the tones of its chunk, the inverse of
+    /// [`Dosr::value_tone`]
+    fn tone_value(&self, tone: usize) -> usize {
+        match self.uses_gray_code() && tone < self.values_per_chunk {
+            true => tone ^ (tone >> 1),
+            false => tone,
+        }
+    }
+
+    fn uses_gray_code(&self) -> bool {
+        self.gray_coding && self.values_per_chunk.is_power_of_two()
+    }
+
+    /// Frequency of the tone carrying the chunk, whatever its amplitude level
+    pub(crate) fn chunk_frequency(&self, chunk: Chunk, chunk_index: usize) -> f32 {
+        let value = chunk as usize % self.values_per_chunk;
+        self.calculate_frequency(value as u8, chunk_index)
+    }
+
+    /// Generates samples for a sine wave with the specified arguments
+    #[inline]
+    fn generate_sine_wave(&self, frequency: f32, amplitude: f32, phase: f64) -> Vec<f32> {
+        self.generate_tone(frequency, amplitude, self.samples_per_frame(), phase)
+    }
+
+    #[cfg(not(feature = "oscillator"))]
+    fn generate_tone(
+        &self,
+        frequency: f32,
+        amplitude: f32,
+        num_samples: usize,
+        phase: f64,
+    ) -> Vec<f32> {
+        (0..num_samples)
+            .map(|n| {
+                let time = n as f32 / self.sample_rate;
+                amplitude * (2.0 * f32::consts::PI * frequency * time + phase as f32).sin()
+            })
+            .collect()
+    }
+
+    /// Rotates a phasor by the phase step of the frequency every sample instead of computing a
+    /// sine per sample. Rotating in double precision keeps the samples within 1e-4 of the
+    /// amplitude of the exact sine, closer than the per-sample sine in single precision.
+    #[cfg(feature = "oscillator")]
+    fn generate_tone(
+        &self,
+        frequency: f32,
+        amplitude: f32,
+        num_samples: usize,
+        phase: f64,
+    ) -> Vec<f32> {
+        let step = std::f64::consts::TAU * frequency as f64 / self.sample_rate as f64;
+        let (sin_step, cos_step) = step.sin_cos();
+        let (mut sin, mut cos) = phase.sin_cos();
+        (0..num_samples)
+            .map(|_| {
+                let sample = amplitude * sin as f32;
+                (cos, sin) = (
+                    cos * cos_step - sin * sin_step,
+                    sin * cos_step + cos * sin_step,
+                );
+                sample
+            })
+            .collect()
+    }
+
+    /// Splits the data into chunks, the remainder of each over the number of values chooses its
+    /// frequency and the quotient its amplitude level. Packed in bits, a final partial chunk is
+    /// padded with zeros.
+    pub(crate) fn bytes_to_chunks(&self, data: &[u8]) -> Vec<Chunk> {
+        let Some(symbol_bits) = self.symbol_bits() else {
+            let (symbols, digits) = (self.symbols(), self.digits_per_byte());
+            return data
+                .iter()
+                .flat_map(|&byte| {
+                    (0..digits)
+                        .rev()
+                        .map(move |i| (byte as usize / symbols.pow(i as u32) % symbols) as Chunk)
+                })
+                .collect_vec();
+        };
+        let data = data
+            .iter()
+            .map(|byte| self.bit_order.arrange(*byte))
+            .collect_vec();
+        data.view_bits::<Msb0>()
+            .chunks(symbol_bits)
+            .map(|c| {
+                c.iter()
+                    .fold(0u8, |acc, bit| (acc << 1) | if *bit { 1 } else { 0 })
+                    << (symbol_bits - c.len())
+            })
+            .collect_vec()
+    }
+
+    /// Groups the chunks into frames, followed by their checksum with per-frame parity. When they
+    /// don't fill the final frame, its trailing chunks are left idle and transmit no tone.
+    pub(crate) fn chunks_to_frames(&self, chunks: &[Chunk]) -> Vec<Frame> {
+        chunks
+            .chunks(self.data_chunks_per_frame())
+            .map(|chunk| {
+                let mut frame = chunk.to_vec();
+                if self.per_frame_parity {
+                    frame.push(self.checksum(chunk));
+                }
+                frame
+            })
+            .collect_vec()
+    }
+
+    /// Number of chunks of a frame that carry data
+    fn data_chunks_per_frame(&self) -> usize {
+        self.chunks_per_frame - self.per_frame_parity as usize
+    }
+
+    /// Sum of the chunks modulo the number of symbols, sent as the last chunk of the frame
+    fn checksum(&self, chunks: &[Chunk]) -> Chunk {
+        (chunks.iter().map(|c| *c as usize).sum::<usize>() % self.symbols()) as Chunk
+    }
+
+    /// Complements every chunk of a frame, so each tone moves to a different frequency
+    fn complement_frame(&self, frame: &Frame) -> Frame {
+        let values = self.values_per_chunk as u8;
+        frame
+            .iter()
+            .map(|v| v - v % values + (values - 1 - v % values))
+            .collect_vec()
+    }
+
+    pub(crate) fn line_encode(&self, frames: Vec<Frame>) -> Vec<Frame> {
+        match self.line_coding {
+            LineCoding::None => frames,
+            LineCoding::Manchester => frames
+                .into_iter()
+                .flat_map(|frame| {
+                    let complement = self.complement_frame(&frame);
+                    [frame, complement]
+                })
+                .collect_vec(),
+        }
+    }
+
+    /// Encodes the ramp-up frames followed by the frames, carrying the phase of every tone slot
+    /// from frame to frame if it's continuous
+    pub(crate) fn encode_frames(&self, frames: impl IntoIterator<Item = Frame>) -> Vec<RawFrame> {
+        let mut phases = vec![0.0; self.chunks_per_frame];
+        let mut encoded = (0..self.ramp_up)
+            .map(|i| self.ramp_up_frame(i, &mut phases))
+            .collect_vec();
+        encoded.extend(
+            frames
+                .into_iter()
+                .map(|frame| self.encode_frame_with_phases(frame, &mut phases)),
+        );
+        encoded
+    }
+
+    /// Single-tone frame, alternating between the lowest and the highest tone of the grid, on
+    /// the first tone slot
+    fn ramp_up_frame(&self, index: usize, phases: &mut [f64]) -> RawFrame {
+        let frequency = if index.is_multiple_of(2) {
+            self.tone_frequency(0)
+        } else {
+            self.tone_frequency(self.tones() - 1)
+        };
+        let mut samples = self.generate_sine_wave(frequency, self.amplitude, phases[0]);
+        self.advance_phase(&mut phases[0], frequency);
+        self.apply_envelope(&mut samples);
+        samples
+    }
+
+    pub(crate) fn encode_frame(&self, frame: Frame) -> RawFrame {
+        self.encode_frame_with_phases(frame, &mut vec![0.0; self.chunks_per_frame])
+    }
+
+    /// Encodes the frame starting the tone of every chunk at the phase of its slot
+    fn encode_frame_with_phases(&self, frame: Frame, phases: &mut [f64]) -> RawFrame {
+        let num_samples = self.samples_per_frame();
+        let mut samples = vec![0.0; num_samples];
+        frame
+            .into_iter()
+            .zip(phases.iter_mut())
+            .enumerate()
+            .map(|(chunk_idx, (v, phase))| {
+                let frequency = self.chunk_frequency(v, chunk_idx);
+                let level = v as usize / self.values_per_chunk + 1;
+                let amplitude = self.amplitude * level as f32 / self.amplitude_levels as f32;
+                let samples = self.generate_sine_wave(frequency, amplitude, *phase);
+                self.advance_phase(phase, frequency);
+                samples
+            })
+            .chain(
+                self.pilot.map(|(frequency, amplitude)| {
+                    self.generate_sine_wave(frequency, amplitude, 0.0)
+                }),
+            )
+            .for_each(|w| {
+                for i in 0..num_samples {
+                    samples[i] += w[i];
+                }
+            });
+        self.apply_envelope(&mut samples);
+        if self.normalize {
+            let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+            if peak > 0.0 {
+                samples.iter_mut().for_each(|s| *s /= peak);
+            }
+        }
+        samples
+    }
+
+    /// Tapers the first and last samples of the frame with the raised-cosine envelope, if any
+    fn apply_envelope(&self, samples: &mut [f32]) {
+        let taper = (self.envelope_rolloff * samples.len() as f32 / 2.0) as usize;
+        for i in 0..taper {
+            let gain = 0.5 - 0.5 * (f32::consts::PI * i as f32 / taper as f32).cos();
+            samples[i] *= gain;
+            samples[samples.len() - 1 - i] *= gain;
+        }
+    }
+
+    /// Moves the phase of a tone slot to the start of the next frame, if it's continuous
+    fn advance_phase(&self, phase: &mut f64, frequency: f32) {
+        if self.continuous_phase {
+            let step = f64::consts::TAU * frequency as f64 / self.sample_rate as f64;
+            *phase = (*phase + step * self.hop() as f64).rem_euclid(f64::consts::TAU);
+        }
+    }
+
+    /// Encrypts the payload with a fresh nonce if a cipher is set
+    pub(crate) fn seal(&self, data: &[u8]) -> Vec<u8> {
+        #[cfg(feature = "crypto")]
+        if self.cipher.is_some() {
+            return self.seal_with_nonce(data, &Cipher::generate_nonce());
+        }
+        self.envelope(PLAINTEXT, data.to_vec())
+    }
+
+    #[cfg(feature = "crypto")]
+    fn seal_with_nonce(&self, data: &[u8], nonce: &[u8; NONCE_LEN]) -> Vec<u8> {
+        match &self.cipher {
+            Some(cipher) => {
+                let protection = match cipher.kind() {
+                    CipherKind::Aes128GcmSiv => AES_128_GCM_SIV,
+                    CipherKind::Aes256GcmSiv => AES_256_GCM_SIV,
+                    CipherKind::ChaCha20Poly1305 => CHACHA20_POLY1305,
+                };
+                self.envelope(protection, cipher.encrypt(nonce, data, &self.aad))
+            }
+            None => self.envelope(PLAINTEXT, data.to_vec()),
+        }
+    }
+
+    /// Wraps the payload in the enabled header, CRC and length prefix, from the inside out, and
+    /// protects the whole with the error correction code
+    fn envelope(&self, protection: u8, payload: Vec<u8>) -> Vec<u8> {
+        let payload =
+            self.prepend_length(self.append_crc(self.prepend_header(protection, payload)));
+        match self.error_correction {
+            ErrorCorrection::None => payload,
+            ErrorCorrection::Hamming74 => fec::hamming74_encode(&payload),
+        }
+    }
+
+    /// Prepends the header naming the protection of the payload, if enabled
+    fn prepend_header(&self, protection: u8, payload: Vec<u8>) -> Vec<u8> {
+        if !self.header {
+            return payload;
+        }
+        [HEADER_MAGIC, (HEADER_VERSION << 4) | protection]
+            .into_iter()
+            .chain(payload)
+            .collect_vec()
+    }
+
+    /// Appends the big-endian CRC32 of the payload, if enabled
+    fn append_crc(&self, mut payload: Vec<u8>) -> Vec<u8> {
+        if self.crc {
+            let crc = CRC32.checksum(&payload);
+            payload.extend(crc.to_be_bytes());
+        }
+        payload
+    }
+
+    /// Prepends the length of the payload, if enabled
+    fn prepend_length(&self, payload: Vec<u8>) -> Vec<u8> {
+        if !self.length_prefix {
+            return payload;
+        }
+        let len =
+            u32::try_from(payload.len()).expect("The payload is too long for its length prefix");
+        len.to_be_bytes().into_iter().chain(payload).collect_vec()
+    }
+
+    pub fn encode_data(&self, data: &[u8]) -> Vec<f32> {
+        self.modulate(&self.seal(data))
+    }
+
+    /// Exact number of samples [`Dosr::encode_data`] produces for a payload of the given length
+    pub fn encoded_len(&self, payload_len: usize) -> usize {
+        #[cfg(feature = "crypto")]
+        let payload_len = match self.cipher {
+            Some(_) => payload_len + Cipher::overhead(),
+            None => payload_len,
+        };
+        let payload_len = payload_len
+            + [
+                (self.header, 2),
+                (self.length_prefix, LENGTH_PREFIX_LEN),
+                (self.crc, CRC_LEN),
+            ]
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, len)| len)
+            .sum::<usize>();
+        let payload_len = match self.error_correction {
+            ErrorCorrection::None => payload_len,
+            ErrorCorrection::Hamming74 => fec::hamming74_len(payload_len),
+        };
+        let chunks = match self.symbol_bits() {
+            Some(symbol_bits) => (payload_len * 8).div_ceil(symbol_bits),
+            None => payload_len * self.digits_per_byte(),
+        };
+        let frames = chunks.div_ceil(self.data_chunks_per_frame());
+        let frames = match self.line_coding {
+            LineCoding::None => frames,
+            LineCoding::Manchester => 2 * frames,
+        } + self.ramp_up;
+        let frames_len = match frames {
+            0 => 0,
+            frames => (frames - 1) * self.hop() + self.samples_per_frame(),
+        };
+        let preamble_len = if self.preamble {
+            self.samples_per_frame()
+        } else {
+            0
+        };
+        self.morse_prefix_len() + preamble_len + frames_len
+    }
+
+    /// Encodes the data into the samples of each frame, before concatenation
+    pub fn encode_data_frames(&self, data: &[u8]) -> Vec<Vec<f32>> {
+        self.encode_labeled_frames(data)
+            .into_iter()
+            .map(|(_, samples)| samples)
+            .collect_vec()
+    }
+
+    /// Encodes the data into the samples of each frame, paired with the chunk values the frame carries.
+    ///
+    /// Ramp-up frames carry no values.
+    pub fn encode_labeled_frames(&self, data: &[u8]) -> Vec<(Vec<u8>, Vec<f32>)> {
+        let frames = self.payload_frames(&self.seal(data));
+        std::iter::repeat_n(vec![], self.ramp_up)
+            .chain(frames.clone())
+            .zip(self.encode_frames(frames))
+            .collect_vec()
+    }
+
+    /// Encodes the data, encrypting it with the given nonce if a cipher is set.
+    ///
+    /// The nonce is ignored when no cipher is set.
+    #[cfg(feature = "crypto")]
+    pub fn encode_data_with_nonce(&self, data: &[u8], nonce: &[u8; NONCE_LEN]) -> Vec<f32> {
+        self.modulate(&self.seal_with_nonce(data, nonce))
+    }
+
+    pub(crate) fn payload_frames(&self, data: &[u8]) -> Vec<Frame> {
+        let chunks = self.bytes_to_chunks(data);
+        self.line_encode(self.chunks_to_frames(&chunks))
+    }
+
+    /// Frame carrying every tone of the grid at once with pseudorandom phases, so its ideal
+    /// spectrum is flat over the grid
+    fn training_frame(&self, index: usize) -> RawFrame {
+        let tones = self.values_per_chunk * self.chunks_per_frame;
+        // As loud as a data frame overall
+        let amplitude = self.amplitude * self.chunks_per_frame as f32 / tones as f32;
+        let mut state = TRAINING_SEED.wrapping_add(index as u32);
+        let mut samples = vec![0.0; self.samples_per_frame()];
+        for tone in 0..tones {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let phase = state as f32 / u32::MAX as f32 * f32::consts::TAU;
+            let frequency = self.tone_frequency(tone);
+            samples.iter_mut().enumerate().for_each(|(n, s)| {
+                let time = n as f32 / self.sample_rate;
+                *s += amplitude * (f32::consts::TAU * frequency * time + phase).sin();
+            });
+        }
+        samples
+    }
+
+    /// Encodes the data after a short sequence of training frames, which let
+    /// [`Dosr::decode_with_training`] flatten the frequency response of the channel
+    pub fn encode_with_training(&self, data: &[u8]) -> Vec<f32> {
+        let training = (0..TRAINING_FRAMES).map(|i| self.training_frame(i));
+        let mut ramp_up = self.encode_frames(self.payload_frames(&self.seal(data)));
+        let payload = ramp_up.split_off(self.ramp_up);
+        self.modulate_frames(ramp_up.into_iter().chain(training).chain(payload))
+    }
+
+    /// Joins independently encoded transmissions into one, padding each to whole frames and
+    /// separating them with silence that [`Dosr::decode_all`] splits them back on.
+    ///
+    /// The gap is rounded up to whole frames, and must last at least a frame.
+    pub fn concat_transmissions(&self, parts: &[Vec<f32>], gap: Duration) -> Vec<f32> {
+        assert!(
+            gap.as_secs_f32() >= self.duration_s,
+            "The gap must last at least a frame"
+        );
+        let samples_per_frame = self.samples_per_frame();
+        let gap_frames = (gap.as_secs_f32() / self.duration_s).ceil() as usize;
+        let gap = vec![0.0; gap_frames * samples_per_frame];
+        let parts = parts.iter().map(|part| {
+            let mut part = part.clone();
+            part.resize(part.len().next_multiple_of(samples_per_frame), 0.0);
+            part
+        });
+        Itertools::intersperse(parts, gap).concat()
+    }
+
+    fn modulate(&self, data: &[u8]) -> Vec<f32> {
+        self.modulate_frames(self.encode_frames(self.payload_frames(data)).into_iter())
+    }
+
+    /// Turns the encoded frames into the transmitted signal
+    pub(crate) fn modulate_frames(&self, frames: impl Iterator<Item = RawFrame>) -> Vec<f32> {
+        let mut samples = self.morse_prefix_samples();
+        samples.extend(self.preamble_samples());
+        samples.extend(self.join_frames(frames));
+        if let Some(alpha) = self.preemphasis {
+            filter::preemphasis(&mut samples, alpha);
+        }
+        filter::limit(&mut samples);
+        samples
+    }
+
+    /// The callsign keyed on the Morse carrier, then silence up to a frame boundary at least a
+    /// word gap later, so that the frames that follow line up with the decoder's
+    fn morse_prefix_samples(&self) -> Vec<f32> {
+        let Some(prefix) = &self.morse_prefix else {
+            return vec![];
+        };
+        let unit = (prefix.unit_s() * self.sample_rate) as usize;
+        let keying = morse::keying(&prefix.text);
+        let mut samples =
+            self.generate_tone(prefix.frequency, self.amplitude, keying.len() * unit, 0.0);
+        for (samples, on) in samples.chunks_mut(unit).zip(keying) {
+            if !on {
+                samples.fill(0.0);
+            }
+        }
+        samples.resize(self.morse_prefix_len(), 0.0);
+        samples
+    }
+
+    /// Frame-long chirp sweeping the tone grid from its lowest tone to its highest, sent ahead of
+    /// the frames when the preamble is enabled
+    pub(crate) fn preamble_samples(&self) -> Vec<f32> {
+        if !self.preamble {
+            return vec![];
+        }
+        let (low, high) = (
+            self.tone_frequency(0) as f64,
+            self.tone_frequency(self.tones() - 1) as f64,
+        );
+        // As loud as a data frame overall
+        let amplitude = self.amplitude * (self.chunks_per_frame as f32).sqrt();
+        let len = self.samples_per_frame();
+        let duration = len as f64 / self.sample_rate as f64;
+        (0..len)
+            .map(|n| {
+                let t = n as f64 / self.sample_rate as f64;
+                let phase = f64::consts::TAU * (low * t + (high - low) * t * t / (2.0 * duration));
+                amplitude * phase.sin() as f32
+            })
+            .collect_vec()
+    }
+
+    fn morse_prefix_len(&self) -> usize {
+        let Some(prefix) = &self.morse_prefix else {
+            return 0;
+        };
+        let unit = (prefix.unit_s() * self.sample_rate) as usize;
+        let keyed = (morse::keying(&prefix.text).len() + morse::WORD_GAP) * unit;
+        keyed.next_multiple_of(self.samples_per_frame())
+    }
+
+    /// Concatenates the frames, or overlap-adds them when frames overlap, a hop apart
+    fn join_frames(&self, frames: impl Iterator<Item = RawFrame>) -> Vec<f32> {
+        if self.overlap == 1 && self.guard_samples() == 0 {
+            return frames.flatten().collect_vec();
+        }
+        let window = match self.overlap {
+            1 => vec![1.0; self.samples_per_frame()],
+            _ => self.window(),
+        };
+        let hop = self.hop();
+        let mut samples: Vec<f32> = vec![];
+        for (i, frame) in frames.enumerate() {
+            let start = i * hop;
+            if samples.len() < start + frame.len() {
+                samples.resize(start + frame.len(), 0.0);
+            }
+            samples[start..]
+                .iter_mut()
+                .zip(frame.iter().zip(&window))
+                .for_each(|(s, (f, w))| *s += f * w);
+        }
+        samples
+    }
+}
+
+/// Decoding functionality
+impl Dosr {
+    fn split_into_frames(&self, samples: &[f32]) -> impl Iterator<Item = RawFrame> {
+        let samples_per_frame = self.samples_per_frame();
+        let window = (self.overlap > 1).then(|| self.window());
+        self.frame_starts(samples).into_iter().map(move |start| {
+            let frame = &samples[start..(start + samples_per_frame).min(samples.len())];
+            match &window {
+                Some(window) => frame.iter().zip(window).map(|(s, w)| s * w).collect_vec(),
+                None => frame.to_vec(),
+            }
+        })
+    }
+
+    fn frame_starts(&self, samples: &[f32]) -> Vec<usize> {
+        let samples_per_frame = self.samples_per_frame();
+        let hop = self.decode_hop();
+        // Overlapping frames are only complete once the next frames have fully started
+        let overlapping = hop < samples_per_frame;
+        let fits = |start: usize| !overlapping || start + samples_per_frame <= samples.len();
+        match self.pilot {
+            Some((pilot, _)) if self.clock_tracking => {
+                self.tracked_frame_starts(samples, pilot, fits)
+            }
+            _ => (0..samples.len())
+                .step_by(hop)
+                .take_while(|start| fits(*start))
+                .collect_vec(),
+        }
+    }
+
+    /// Starts of the frames of a recording whose sample clock drifts, each realigned on the
+    /// pilot's phase and the next predicted from the tracked frame length
+    fn tracked_frame_starts(
+        &self,
+        samples: &[f32],
+        pilot: Frequency,
+        fits: impl Fn(usize) -> bool,
+    ) -> Vec<usize> {
+        let samples_per_frame = self.samples_per_frame();
+        let hop = self.decode_hop() as f64;
+        let mut starts = vec![];
+        let (mut position, mut rate, mut previous) = (0.0f64, 1.0f64, None::<f64>);
+        loop {
+            let start = position.round().max(0.0) as usize;
+            if start >= samples.len() || !fits(start) {
+                break;
+            }
+            let measured = samples
+                .get(start..start + samples_per_frame)
+                .and_then(|frame| self.pilot_timing_error(frame, pilot));
+            match measured {
+                Some(error) => {
+                    position -= error as f64;
+                    if let Some(previous) = previous {
+                        let length = (position - previous) / hop;
+                        if (length - 1.0).abs() < MAX_CLOCK_DRIFT {
+                            rate += CLOCK_SMOOTHING * (length - rate);
+                        }
+                    }
+                    previous = Some(position);
+                }
+                None => previous = None,
+            }
+            let start = position.round().max(0.0) as usize;
+            if start < samples.len() {
+                starts.push(start);
+            }
+            position += hop * rate;
+        }
+        starts
+    }
+
+    /// Number of samples the frame starts after the frame the pilot's phase says it should, within
+    /// half a pilot period
+    fn pilot_timing_error(&self, frame: &[f32], pilot: Frequency) -> Option<f32> {
+        if self.is_silent(frame) {
+            return None;
+        }
+        let omega = f32::consts::TAU * pilot / self.sample_rate;
+        // Windowed so the leakage of the nearby tones doesn't shift the phase
+        let (re, im) = frame.iter().zip(self.window()).enumerate().fold(
+            (0.0, 0.0),
+            |(re, im), (n, (s, w))| {
+                let phase = omega * n as f32;
+                (re + w * s * phase.cos(), im - w * s * phase.sin())
+            },
+        );
+        // A sine starting at phase zero correlates a quarter turn behind the cosine
+        let phase = im.atan2(re) + f32::consts::FRAC_PI_2;
+        let phase = (phase + f32::consts::PI).rem_euclid(f32::consts::TAU) - f32::consts::PI;
+        Some(phase / omega)
+    }
+
+    /// Prepares the whole received signal for framing
+    pub(crate) fn condition_signal<'a>(&self, samples: &'a [f32]) -> Cow<'a, [f32]> {
+        let mut samples = Cow::Borrowed(samples);
+        // Corrupt inputs may contain NaN or infinite samples, which would poison the whole FFT
+        if samples.iter().any(|s| !s.is_finite()) {
+            samples
+                .to_mut()
+                .iter_mut()
+                .filter(|s| !s.is_finite())
+                .for_each(|s| *s = 0.0);
+        }
+        if self.dc_block {
+            filter::dc_block(samples.to_mut(), DC_BLOCK_POLE);
+        }
+        if self.bandpass {
+            let (low, high) = self.passband();
+            filter::bandpass(samples.to_mut(), low, high, self.sample_rate);
+        }
+        if let Some(alpha) = self.preemphasis {
+            filter::deemphasis(samples.to_mut(), alpha);
+        }
+        samples
+    }
+
+    /// Edges of the band the bandpass filter passes (Hz), the tones and the pilot with a margin
+    fn passband(&self) -> (f32, f32) {
+        let pilot = self.pilot.map(|(frequency, _)| frequency);
+        let lowest = pilot.map_or(self.tone_frequency(0), |p| p.min(self.tone_frequency(0)));
+        let highest = self.tone_frequency(self.tones() - 1);
+        let highest = pilot.map_or(highest, |p| p.max(highest));
+        let margin = BANDPASS_MARGIN * self.tone_spacing();
+        ((lowest - margin).max(1.0), highest + margin)
+    }
+
+    /// Whether the frame carries no tones at all, e.g. the gap between two transmissions
+    pub(crate) fn is_silent(&self, frame: &[f32]) -> bool {
+        let energy = frame
+            .iter()
+            .filter(|s| s.is_finite())
+            .map(|s| s * s)
+            .sum::<f32>()
+            / frame.len().max(1) as f32;
+        energy.sqrt() < self.silence_rms
+    }
+
+    /// Splits the recording into the transmissions it contains, separated by at least a frame
+    /// of silence
+    fn segments<'a>(&self, samples: &'a [f32]) -> Vec<&'a [f32]> {
+        let samples_per_frame = self.samples_per_frame();
+        // Look for silence in blocks much shorter than a frame, to locate the start of every
+        // transmission precisely enough
+        let block = (samples_per_frame / 32).max(1);
+        let gap = samples_per_frame.div_ceil(block);
+        let mut ranges = vec![];
+        let (mut start, mut end, mut silent_blocks) = (None, 0, 0);
+        for (i, chunk) in samples.chunks(block).enumerate() {
+            if !self.is_silent(chunk) {
+                start.get_or_insert(i * block);
+                end = i * block + chunk.len();
+                silent_blocks = 0;
+                continue;
+            }
+            silent_blocks += 1;
+            if silent_blocks >= gap
+                && let Some(start) = start.take()
+            {
+                ranges.push((start, end));
+            }
+        }
+        if let Some(start) = start {
+            ranges.push((start, end));
+        }
+        // Blocks only locate the transmissions to within a block, round them to whole frames and
+        // the guard intervals between them
+        let guard = self.guard_samples();
+        let stride = samples_per_frame + guard;
+        ranges
+            .into_iter()
+            .map(|(start, end)| {
+                let frames = ((end - start + guard) as f32 / stride as f32).round();
+                let end = start + (frames as usize).max(1) * stride - guard;
+                &samples[start..end.min(samples.len())]
+            })
+            .collect_vec()
+    }
+
+    /// Splits the conditioned signal into frames, preparing them for detection
+    fn receive_frames(&self, samples: &[f32]) -> impl Iterator<Item = RawFrame> {
+        let mut agc_state = AgcState::default();
+        self.split_into_frames(samples).map(move |mut frame| {
+            if let Some(agc) = &self.agc {
+                agc_state.apply(agc, &mut frame);
+            }
+            frame
+        })
+    }
+
+    /// Offset of the first frame that lines the frames up best with the symbols
+    fn timing_offset(&self, samples: &[f32]) -> usize {
+        if self.timing_steps == 1 {
+            return 0;
+        }
+        let step = (self.samples_per_frame() / self.timing_steps).max(1);
+        (0..self.timing_steps)
+            .map(|i| i * step)
+            .filter(|offset| *offset < samples.len())
+            .map(|offset| (offset, self.peak_energy(&samples[offset..])))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(offset, _)| offset)
+    }
+
+    /// Total energy of the detected tones. Frames straddling two symbols split the energy of
+    /// their tones over twice as many peaks, so it is the highest when the frames line up.
+    fn peak_energy(&self, samples: &[f32]) -> f32 {
+        let bin_width = self.sample_rate / self.samples_per_frame() as f32;
+        self.receive_frames(samples)
+            .filter(|frame| frame.len() == self.samples_per_frame())
+            .map(|frame| {
+                let magnitudes = self.magnitudes(frame.as_slice());
+                let normalized = self.normalize_magnitudes(magnitudes.clone());
+                self.peak_bins(&normalized, bin_width)
+                    .into_iter()
+                    .map(|i| magnitudes[i].powi(2))
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+
+    /// Receives the frames past the ramp-up. The receiver may have missed the first ramp-up
+    /// frames, so it stops at the first frame with as many tones as chunks.
+    pub(crate) fn receive_payload(&self, samples: &[f32]) -> Vec<RawFrame> {
+        let start = self
+            .find_frame_start(samples)
+            .unwrap_or_else(|| self.timing_offset(samples));
+        let samples = &samples[start..];
+        let mut frames = self.receive_frames(samples).collect_vec();
+        if self.morse_prefix.is_some() {
+            // The Morse prefix is silence and a single tone off the grid
+            let prefix = frames
+                .iter()
+                .take_while(|frame| {
+                    self.is_silent(frame)
+                        || self
+                            .detect_frequencies(frame)
+                            .iter()
+                            .all(|f| self.chunk_band(*f).is_none())
+                })
+                .count();
+            frames.drain(..prefix);
+        }
+        let ramp_up = frames
+            .iter()
+            .take(self.ramp_up)
+            .take_while(|frame| self.detect_frequencies(frame).len() < self.chunks_per_frame)
+            .count();
+        frames.drain(..ramp_up);
+        frames
+    }
+
+    fn perform_fft<T: Planned>(&self, encoded_frame: &[T]) -> Vec<Complex<T>> {
+        let fft = self.fft.get(encoded_frame.len(), self.samples_per_frame());
+        let mut buffer = encoded_frame
+            .iter()
+            .map(|s| Complex::new(*s, T::zero()))
+            .collect_vec();
+        fft.process(&mut buffer);
+        buffer
+    }
+
+    /// Number of bins of the spectrum of a frame of the given length up to the scan limit, at most
+    /// up to the Nyquist frequency
+    fn scanned_bins(&self, len: usize) -> usize {
+        let half = len / 2;
+        let Some(margin) = self.scan_margin else {
+            return half;
+        };
+        let highest = self
+            .pilot
+            .map_or(0.0, |(frequency, _)| frequency)
+            .max(self.tone_frequency(self.tones() - 1));
+        let bin_width = self.sample_rate / len as f32;
+        // One more bin for the neighbour the peak of the highest tone is compared with
+        half.min(((highest + margin) / bin_width).ceil() as usize + 2)
+    }
+
+    fn magnitudes<T: Planned + Float>(&self, frame: &[T]) -> Vec<T> {
+        let half = self.scanned_bins(frame.len());
+        if self.welch_segments <= 1 || frame.len() < self.welch_segments + 1 {
+            return self
+                .perform_fft(frame)
+                .iter()
+                .take(half)
+                .map(|c| c.norm())
+                .collect_vec();
+        }
+        // Each sub-window is zero-padded to the frame length so its bins line up with the frame's
+        let hop = frame.len() / (self.welch_segments + 1);
+        let mut sums = vec![T::zero(); half];
+        for segment in 0..self.welch_segments {
+            let mut padded = vec![T::zero(); frame.len()];
+            let window = segment * hop..(segment + 2) * hop;
+            padded[window.clone()].copy_from_slice(&frame[window]);
+            for (sum, c) in sums.iter_mut().zip(self.perform_fft(&padded)) {
+                *sum = *sum + c.norm();
+            }
+        }
+        sums
+    }
+
+    fn normalize_magnitudes<T: FftNum + Float>(&self, magnitudes: Vec<T>) -> Vec<T> {
+        let max_magnitude = magnitudes.iter().cloned().fold(T::zero(), T::max);
+        // Digital silence has no magnitude to normalize by
+        if max_magnitude == T::zero() {
+            return magnitudes;
+        }
+        magnitudes.iter().map(|m| *m / max_magnitude).collect_vec()
+    }
+
+    /// Normalized magnitude spectrum of the frame up to the Nyquist frequency, computed with
+    /// the configured precision
+    fn spectrum(&self, frame: &[f32]) -> Vec<f32> {
+        match self.precision {
+            Precision::Single => self.normalize_magnitudes(self.magnitudes(frame)),
+            Precision::Double => {
+                let frame = frame.iter().map(|s| *s as f64).collect_vec();
+                self.normalize_magnitudes(self.magnitudes(&frame))
+                    .into_iter()
+                    .map(|m| m as f32)
+                    .collect_vec()
+            }
+        }
+    }
+
+    /// Index of the chunk whose frequency band contains the frequency
+    fn chunk_band(&self, freq: f32) -> Option<usize> {
+        self.nearest_tone(freq)
+            .map(|tone| tone / self.values_per_chunk)
+    }
+
+    fn peak_threshold(&self, freq: f32) -> f32 {
+        if self.band_thresholds.is_empty() {
+            return self.peak_threshold;
+        }
+        self.band_threshold(self.chunk_band(freq))
+    }
+
+    fn band_threshold(&self, chunk: Option<usize>) -> f32 {
+        chunk
+            .and_then(|chunk| self.band_thresholds.get(chunk))
+            .copied()
+            .unwrap_or(self.peak_threshold)
+    }
+
+    /// Chunk band of every bin of a spectrum, looked up once per spectrum layout rather than
+    /// for every bin of every frame, since a scale makes each lookup a scan of the grid
+    fn bin_bands(&self, bins: usize, bin_width: f32) -> BinBands {
+        let key = BinBandsKey {
+            bins,
+            bin_width,
+            base_freq: self.base_freq,
+            delta_freq: self.delta_freq,
+            scale: self.scale,
+            values_per_chunk: self.values_per_chunk,
+            chunks_per_frame: self.chunks_per_frame,
+        };
+        let mut cache = self
+            .bin_bands
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some((cached, bands)) = &*cache
+            && *cached == key
+        {
+            return bands.clone();
+        }
+        let bands = (0..bins)
+            .map(|i| self.chunk_band(i as f32 * bin_width))
+            .collect::<BinBands>();
+        *cache = Some((key, bands.clone()));
+        bands
+    }
+
+    /// Returns the bins of the local maxima that stand out of the normalized spectrum. The
+    /// neighbours past either end of the spectrum count as zero.
+    pub(crate) fn detect_peaks(&self, magnitudes: &[f32], bin_width: f32) -> Vec<usize> {
+        let bands =
+            (!self.band_thresholds.is_empty()).then(|| self.bin_bands(magnitudes.len(), bin_width));
+        let mut peaks = vec![];
+        for (i, &mag) in magnitudes.iter().enumerate() {
+            let threshold = match &bands {
+                Some(bands) => self.band_threshold(bands[i]),
+                None => self.peak_threshold,
+            };
+            let below = i.checked_sub(1).map_or(0.0, |j| magnitudes[j]);
+            let above = magnitudes.get(i + 1).copied().unwrap_or(0.0);
+            if mag > threshold && mag > below && mag > above {
+                peaks.push(i);
+            }
+        }
+        if self.harmonic_suppression {
+            self.suppress_harmonics(magnitudes, peaks)
+        } else {
+            peaks
+        }
+    }
+
+    /// Drops the weakest peak explained as a harmonic of a stronger lower one, for as long as
+    /// there are more peaks than chunks. Frames with the expected number of peaks are left
+    /// alone, since a tone may legitimately sit at a multiple of another.
+    fn suppress_harmonics(&self, magnitudes: &[f32], mut peaks: Vec<usize>) -> Vec<usize> {
+        let is_harmonic = |peak: usize, fundamental: usize| {
+            let k = (peak as f32 / fundamental as f32).round();
+            // Bin rounding errors of the fundamental are multiplied in its harmonics
+            k >= 2.0 && (peak as f32 - k * fundamental as f32).abs() <= (k + 1.0) / 2.0
+        };
+        while peaks.len() > self.chunks_per_frame {
+            let harmonic = peaks
+                .iter()
+                .copied()
+                .filter(|&peak| {
+                    peaks.iter().any(|&fundamental| {
+                        fundamental > 0
+                            && fundamental < peak
+                            && magnitudes[fundamental] > magnitudes[peak]
+                            && is_harmonic(peak, fundamental)
+                    })
+                })
+                .min_by(|a, b| magnitudes[*a].total_cmp(&magnitudes[*b]));
+            match harmonic {
+                Some(harmonic) => peaks.retain(|&peak| peak != harmonic),
+                None => break,
+            }
+        }
+        peaks
+    }
+
+    /// Bins of the tones found by the peak detector
+    fn peak_bins(&self, magnitudes: &[f32], bin_width: f32) -> Vec<usize> {
+        let frequencies = self.peak_detector.detect(magnitudes, bin_width, self);
+        self.bins_of(&frequencies, bin_width, magnitudes.len())
+    }
+
+    /// Nearest spectrum bin of every frequency, dropping those past the last bin
+    fn bins_of(&self, frequencies: &[Frequency], bin_width: f32, bins: usize) -> Vec<usize> {
+        frequencies
+            .iter()
+            .map(|f| (f / bin_width).round() as usize)
+            .filter(|i| *i < bins)
+            .collect_vec()
+    }
+
+    fn detect_frequencies(&self, samples: &[f32]) -> Vec<Frequency> {
+        if self.is_silent(samples) {
+            return vec![];
+        }
+        if self.detection_method == DetectionMethod::Goertzel {
+            return self.detect_frequencies_goertzel(samples);
+        }
+        let magnitudes = self.spectrum(samples);
+        let bin_width = self.sample_rate / samples.len() as f32;
+        self.peak_detector.detect(&magnitudes, bin_width, self)
+    }
+
+    /// Detects the tones of the frame with a Goertzel filter at every frequency of the tone grid,
+    /// which beats a full spectrum on grids of a few tones and frames whose length doesn't suit
+    /// the FFT. The magnitudes are normalized to the strongest tone and compared with the
+    /// detection thresholds.
+    pub fn detect_frequencies_goertzel(&self, frame: &[f32]) -> Vec<f32> {
+        let frequencies = (0..self.tones())
+            .map(|tone| self.tone_frequency(tone) / self.sample_rate)
+            .collect_vec();
+        let magnitudes = goertzel(frame, &frequencies);
+        let max_magnitude = magnitudes.iter().cloned().fold(0.0f32, f32::max);
+        if max_magnitude == 0.0 {
+            return vec![];
+        }
+        magnitudes
+            .into_iter()
+            .enumerate()
+            .map(|(tone, magnitude)| (self.tone_frequency(tone), magnitude / max_magnitude))
+            .filter(|(frequency, magnitude)| *magnitude > self.peak_threshold(*frequency))
+            .map(|(frequency, _)| frequency)
+            .collect_vec()
+    }
+
+    /// Magnitude spectrum of every frame over the band of the tone grid, normalized per frame,
+    /// for displays such as a waterfall.
+    pub fn band_spectrogram(&self, samples: &[f32]) -> Vec<Vec<f32>> {
+        self.band_spectrogram_with_fft_size(samples, self.samples_per_frame())
+    }
+
+    /// Like [`Dosr::band_spectrogram`], zero-padding every frame to the given FFT size for a
+    /// finer frequency grid. Only the display is affected, decoding keeps using frame-sized FFTs.
+    pub fn band_spectrogram_with_fft_size(
+        &self,
+        samples: &[f32],
+        fft_size: usize,
+    ) -> Vec<Vec<f32>> {
+        assert!(
+            fft_size >= self.samples_per_frame(),
+            "The FFT size must be at least a frame long"
+        );
+        let samples = self.condition_signal(samples);
+        let bin_width = self.sample_rate / fft_size as f32;
+        let (low, high) = (
+            (self.tone_frequency(0) / bin_width).floor() as usize,
+            (self.tone_frequency(self.tones() - 1) / bin_width).ceil() as usize + 1,
+        );
+        self.receive_frames(&samples)
+            .filter(|frame| frame.len() == self.samples_per_frame())
+            .map(|mut frame| {
+                frame.resize(fft_size, 0.0);
+                let spectrum = self.spectrum(&frame);
+                spectrum[low.min(spectrum.len())..high.min(spectrum.len())].to_vec()
+            })
+            .collect_vec()
+    }
+
+    /// Frequencies of the tones detected in every frame, in ascending order, before they are
+    /// mapped to values. Useful to spot a misconfigured grid, an offset or drift.
+    pub fn detected_frequency_series(&self, samples: &[f32]) -> Vec<Vec<f32>> {
+        let samples = self.condition_signal(samples);
+        self.receive_frames(&samples)
+            .map(|frame| self.detect_frequencies(&frame))
+            .collect_vec()
+    }
+
+    /// Returns the frequency of the strongest tone in the frame, ignoring the DC bin.
+    ///
+    /// Non-finite magnitudes are skipped, so an empty or silent frame, or one holding non-finite
+    /// samples, has no dominant frequency.
+    pub fn detect_dominant_frequency(&self, frame: &[f32]) -> Option<f32> {
+        if frame.is_empty() {
+            return None;
+        }
+        let bin_width = self.sample_rate / frame.len() as f32;
+        self.spectrum(frame)
+            .into_iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, m)| m.is_finite() && *m > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i as f32 * bin_width)
+    }
+
+    fn decode_frequency(&self, freq: f32, chunk_index: usize) -> u8 {
+        let value = match self.scale {
+            Some(_) => self.nearest_tone(freq).unwrap_or_default(),
+            None => ((freq - self.base_freq) / self.delta_freq).round() as usize,
+        };
+        // A spurious tone can sit below the chunk's band, which only corrupts this frame
+        let value = value.wrapping_sub(self.values_per_chunk * chunk_index);
+        self.tone_value(value) as u8
+    }
+
+    fn decode_frequencies(&self, frequencies: &[Frequency]) -> Frame {
+        // Missed or spurious tones would shift the position of every following tone, and frames
+        // with idle chunks have fewer tones, so find the chunk of each tone by its band instead.
+        // The first tone of a band wins, the chunks of missed tones read as 0 and the trailing
+        // ones are left out as idle.
+        if frequencies.len() != self.chunks_per_frame {
+            let mut values = vec![None; self.chunks_per_frame];
+            for f in frequencies {
+                if let Some(chunk_idx) = self.chunk_band(*f) {
+                    values[chunk_idx].get_or_insert_with(|| self.decode_frequency(*f, chunk_idx));
+                }
+            }
+            let len = values
+                .iter()
+                .rposition(Option::is_some)
+                .map_or(0, |i| i + 1);
+            return values[..len].iter().map(|v| v.unwrap_or(0)).collect_vec();
+        }
+        frequencies
+            .iter()
+            .enumerate()
+            .map(|(chunk_idx, f)| self.decode_frequency(*f, chunk_idx))
+            .collect_vec()
+    }
+
+    /// Decodes a vector of frequencies into a frame.
+    fn decode_frame(&self, samples: &RawFrame, offset: f32) -> Frame {
+        self.decode_frequencies(&self.correct_offset(self.detect_frequencies(samples), offset))
+    }
+
+    /// Decodes the frames, following the pilot tone if there's one
+    pub(crate) fn decode_frames(&self, frames: &[RawFrame]) -> Vec<Frame> {
+        frames
+            .iter()
+            .zip(self.pilot_offsets(frames))
+            .map(|(frame, offset)| self.decode_frame(frame, offset))
+            .collect_vec()
+    }
+
+    /// Frequency offset of every frame, measured on the pilot tone and following its drift from
+    /// frame to frame. Zero without a pilot.
+    fn pilot_offsets(&self, frames: &[RawFrame]) -> Vec<f32> {
+        let Some((pilot, _)) = self.pilot else {
+            return vec![0.0; frames.len()];
+        };
+        let mut offset = 0.0;
+        frames
+            .iter()
+            .map(|frame| {
+                if let Some(frequency) = self.find_pilot(frame, pilot + offset) {
+                    offset = frequency - pilot;
+                }
+                offset
+            })
+            .collect_vec()
+    }
+
+    /// Frequency of the strongest peak within half a tone spacing of where the pilot is expected,
+    /// interpolated between bins
+    fn find_pilot(&self, frame: &[f32], expected: f32) -> Option<f32> {
+        if self.is_silent(frame) {
+            return None;
+        }
+        let magnitudes = self.spectrum(frame);
+        let bin_width = self.sample_rate / frame.len() as f32;
+        let low = ((expected - self.delta_freq / 2.0) / bin_width)
+            .floor()
+            .max(1.0) as usize;
+        let high = (((expected + self.delta_freq / 2.0) / bin_width).ceil() as usize)
+            .min(magnitudes.len().saturating_sub(2));
+        let peak = (low..=high).max_by(|a, b| magnitudes[*a].total_cmp(&magnitudes[*b]))?;
+        let (a, b, c) = (magnitudes[peak - 1], magnitudes[peak], magnitudes[peak + 1]);
+        let denominator = a - 2.0 * b + c;
+        let shift = if denominator.abs() > f32::EPSILON {
+            (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        Some((peak as f32 + shift) * bin_width)
+    }
+
+    /// Removes the frequency offset from the detected tones, leaving the pilot out
+    fn correct_offset(&self, frequencies: Vec<Frequency>, offset: f32) -> Vec<Frequency> {
+        let Some((pilot, _)) = self.pilot else {
+            return frequencies;
+        };
+        frequencies
+            .into_iter()
+            .map(|frequency| frequency - offset)
+            .filter(|frequency| (frequency - pilot).abs() > self.delta_freq / 2.0)
+            .collect_vec()
+    }
+
+    /// Per-bin gains that flatten the spectrum the training frames were received with
+    fn equalizer(&self, training: &[RawFrame]) -> Vec<f32> {
+        let spectra = training.iter().map(|f| self.spectrum(f)).collect_vec();
+        let bins = self.samples_per_frame() / 2;
+        let bin_width = self.sample_rate / self.samples_per_frame() as f32;
+        let anchors = (0..self.values_per_chunk * self.chunks_per_frame)
+            .map(|tone| {
+                let frequency = self.tone_frequency(tone);
+                let bin = (frequency / bin_width).round() as usize;
+                let received = spectra
+                    .iter()
+                    .map(|spectrum| spectrum.get(bin).copied().unwrap_or(0.0))
+                    .sum::<f32>()
+                    / spectra.len() as f32;
+                (bin, 1.0 / received.max(MIN_TRAINING_LEVEL))
+            })
+            .filter(|(bin, _)| *bin < bins)
+            .collect_vec();
+        // Interpolate between the tones of the grid, holding the gains of its edges beyond it
+        (0..bins)
+            .map(|bin| match anchors.iter().position(|(b, _)| *b >= bin) {
+                None => anchors.last().map_or(1.0, |(_, gain)| *gain),
+                Some(0) => anchors[0].1,
+                Some(i) => {
+                    let ((b0, g0), (b1, g1)) = (anchors[i - 1], anchors[i]);
+                    g0 + (g1 - g0) * (bin - b0) as f32 / (b1 - b0).max(1) as f32
+                }
+            })
+            .collect_vec()
+    }
+
+    /// Decodes a frame after flattening its spectrum with the equalizer
+    fn decode_equalized_frame(&self, samples: &RawFrame, equalizer: &[f32]) -> Frame {
+        // The equalizer's bins only line up with full frames
+        if samples.len() != self.samples_per_frame() {
+            return self.decode_frame(samples, 0.0);
+        }
+        let magnitudes = self
+            .spectrum(samples)
+            .iter()
+            .zip(equalizer)
+            .map(|(m, gain)| m * gain)
+            .collect_vec();
+        let max_magnitude = magnitudes.iter().cloned().fold(0.0f32, f32::max);
+        // Digital silence has no magnitude to normalize by, nor tones
+        if max_magnitude <= 0.0 {
+            return vec![];
+        }
+        let magnitudes = magnitudes.iter().map(|m| m / max_magnitude).collect_vec();
+        let bin_width = self.sample_rate / samples.len() as f32;
+        let frequencies = self.peak_detector.detect(&magnitudes, bin_width, self);
+        self.decode_frequencies(&frequencies)
+    }
+
+    /// Decodes a frame while measuring how clearly its tones were received
+    fn decode_frame_with_metrics(&self, samples: &RawFrame, offset: f32) -> FrameMetrics {
+        if self.is_silent(samples) {
+            return FrameMetrics {
+                detected: 0,
+                unexpected_tones: false,
+                values: vec![],
+                snr_db: f32::NEG_INFINITY,
+                low_confidence: true,
+            };
+        }
+        let magnitudes = self.spectrum(samples);
+        let bin_width = self.sample_rate / samples.len() as f32;
+        let frequencies = self.peak_detector.detect(&magnitudes, bin_width, self);
+        let peaks = self.bins_of(&frequencies, bin_width, magnitudes.len());
+        let frequencies = self.correct_offset(frequencies, offset);
+        let values = self.decode_frequencies(&frequencies);
+
+        let signal = peaks.iter().map(|i| magnitudes[*i]).sum::<f32>() / peaks.len() as f32;
+        let noise = magnitudes
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !peaks.contains(i))
+            .map(|(_, m)| m)
+            .sum::<f32>()
+            / (magnitudes.len() - peaks.len()) as f32;
+        let snr_db = 20.0 * (signal / noise).log10();
+
+        FrameMetrics {
+            detected: frequencies.len(),
+            unexpected_tones: frequencies.len() != self.chunks_per_frame,
+            low_confidence: frequencies.len() != self.chunks_per_frame
+                || snr_db.is_nan()
+                || snr_db < LOW_CONFIDENCE_SNR_DB,
+            values,
+            snr_db,
+        }
+    }
+
+    /// Undoes the line code, recovering each data frame from the frames that carry it
+    fn line_decode(&self, frames: Vec<Frame>) -> Vec<Frame> {
+        match self.line_coding {
+            LineCoding::None => frames,
+            LineCoding::Manchester => frames
+                .chunks(2)
+                .map(|pair| match pair {
+                    // Trust whichever half had more of its tones detected
+                    [data, complement] if complement.len() > data.len() => {
+                        self.complement_frame(complement)
+                    }
+                    [data, ..] => data.clone(),
+                    [] => unreachable!(),
+                })
+                .collect_vec(),
+        }
+    }
+
+    /// Amplitude of the tone at the given frequency, from its correlation with the frame
+    fn tone_amplitude(&self, samples: &[f32], frequency: f32) -> f32 {
+        let omega = f32::consts::TAU * frequency / self.sample_rate;
+        let (re, im) = samples
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (n, s)| {
+                let phase = omega * n as f32;
+                (re + s * phase.cos(), im - s * phase.sin())
+            });
+        2.0 * (re * re + im * im).sqrt() / samples.len().max(1) as f32
+    }
+
+    /// With amplitude levels, replaces the values detected from the peaks with the tone of
+    /// each band and its level. The lower levels can fall below the peak detection threshold,
+    /// so the tone of a band is the strongest of its frequencies instead.
+    pub(crate) fn detect_levels(&self, samples: &[RawFrame], frames: Vec<Frame>) -> Vec<Frame> {
+        if self.amplitude_levels == 1 {
+            return frames;
+        }
+        let tones = samples
+            .iter()
+            .map(|frame| self.band_tones(frame))
+            .collect_vec();
+        // The strongest tone of the signal is at the top level
+        let reference = tones
+            .iter()
+            .flatten()
+            .map(|(_, amplitude)| *amplitude)
+            .fold(0.0f32, f32::max);
+        let levels = self.amplitude_levels as f32;
+        tones
+            .into_iter()
+            .map(|tones| {
+                tones
+                    .into_iter()
+                    .map(|(v, amplitude)| {
+                        let level = (amplitude / reference * levels).round().clamp(1.0, levels);
+                        (v as usize + (level as usize - 1) * self.values_per_chunk) as Chunk
+                    })
+                    .collect_vec()
+            })
+            .collect_vec()
+    }
+
+    /// Value and amplitude of the strongest tone of each chunk's band, up to the first band
+    /// without any tone standing out
+    fn band_tones(&self, samples: &[f32]) -> Vec<(Chunk, f32)> {
+        let magnitudes = self.spectrum(samples);
+        let bin_width = self.sample_rate / samples.len() as f32;
+        (0..self.chunks_per_frame)
+            .map_while(|chunk_idx| {
+                let (v, magnitude) = (0..self.values_per_chunk)
+                    .map(|v| {
+                        let frequency = self.calculate_frequency(v as u8, chunk_idx);
+                        let bin = (frequency / bin_width).round() as usize;
+                        (v as u8, magnitudes.get(bin).copied().unwrap_or(0.0))
+                    })
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+                let frequency = self.calculate_frequency(v, chunk_idx);
+                let threshold = self.peak_threshold(frequency) / self.amplitude_levels as f32;
+                (magnitude > threshold).then(|| (v, self.tone_amplitude(samples, frequency)))
+            })
+            .collect_vec()
+    }
+
+    fn frames_to_bytes(&self, frames: Vec<Frame>) -> Vec<u8> {
+        self.chunks_to_bytes(self.data_chunks(frames))
+    }
+
+    /// Chunks of data carried by the frames, without the line code and the checksums
+    fn data_chunks(&self, frames: Vec<Frame>) -> impl Iterator<Item = Chunk> {
+        let parity = self.per_frame_parity as usize;
+        let mut frames = self.line_decode(frames);
+        // Only the final frame has idle chunks, the trailing tones missed in any other one read
+        // as 0 rather than shifting the chunks of the frames after it
+        if let Some(last) = frames.iter().rposition(|frame| !frame.is_empty()) {
+            frames[..last]
+                .iter_mut()
+                .filter(|frame| !frame.is_empty())
+                .for_each(|frame| frame.resize(self.chunks_per_frame, 0));
+        }
+        frames.into_iter().flat_map(move |mut frame| {
+            frame.truncate(frame.len().saturating_sub(parity));
+            frame
+        })
+    }
+
+    /// Whether the chunks may be the encoding of a whole number of bytes. The final frame only
+    /// ends partway through a byte when some of its tones were missed.
+    fn whole_bytes(&self, chunks: usize) -> bool {
+        match self.symbol_bits() {
+            // The final chunk is padded with fewer bits than a chunk holds
+            Some(symbol_bits) => chunks * symbol_bits % 8 < symbol_bits,
+            None => chunks.is_multiple_of(self.digits_per_byte()),
+        }
+    }
+
+    /// Indices of the data frames whose checksum doesn't match their chunks
+    fn corrupt_frames(&self, frames: Vec<Frame>) -> Vec<usize> {
+        if !self.per_frame_parity {
+            return vec![];
+        }
+        self.line_decode(frames)
+            .iter()
+            .enumerate()
+            .filter(|(_, frame)| {
+                frame
+                    .split_last()
+                    .is_some_and(|(checksum, data)| *checksum != self.checksum(data))
+            })
+            .map(|(i, _)| i)
+            .collect_vec()
+    }
+
+    /// Recovers the payload from the frames, retrying with the other bit order if enabled and
+    /// the payload fails its checks. Also returns the bit order the payload was read in.
+    pub(crate) fn frames_to_payload(
+        &self,
+        frames: Vec<Frame>,
+    ) -> (Result<Vec<u8>, DecodeError>, BitOrder) {
+        let chunks = self.data_chunks(frames).collect_vec();
+        if !self.whole_bytes(chunks.len()) {
+            let received = self.chunks_to_bytes(chunks).len();
+            let truncated = DecodeError::Truncated {
+                declared: None,
+                received,
+            };
+            return (Err(truncated), self.bit_order);
+        }
+        let payload = self.decrypt(self.chunks_to_bytes(chunks.iter().copied()));
+        if self.bit_order_detection
+            && matches!(
+                payload,
+                Err(DecodeError::InvalidHeader { .. }
+                    | DecodeError::AuthFailed { .. }
+                    | DecodeError::Truncated { .. }
+                    | DecodeError::ChecksumMismatch { .. })
+            )
+        {
+            let other = self.bit_order.other();
+            let retry = self.decrypt(self.chunks_to_bytes_in(chunks, other));
+            if retry.is_ok() {
+                return (retry, other);
+            }
+        }
+        (payload, self.bit_order)
+    }
+
+    /// Reassembles the bytes from the chunks, reversing [`Dosr::bytes_to_chunks`]
+    pub(crate) fn chunks_to_bytes(&self, chunks: impl IntoIterator<Item = Chunk>) -> Vec<u8> {
+        self.chunks_to_bytes_in(chunks, self.bit_order)
+    }
+
+    fn chunks_to_bytes_in(
+        &self,
+        chunks: impl IntoIterator<Item = Chunk>,
+        bit_order: BitOrder,
+    ) -> Vec<u8> {
+        let chunks = chunks.into_iter();
+        let Some(symbol_bits) = self.symbol_bits() else {
+            let symbols = self.symbols();
+            // A final partial byte is dropped, and corrupt digits wrap around
+            return chunks
+                .chunks(self.digits_per_byte())
+                .into_iter()
+                .map(|digits| digits.collect_vec())
+                .filter(|digits| digits.len() == self.digits_per_byte())
+                .map(|digits| {
+                    digits
+                        .iter()
+                        .fold(0usize, |acc, d| acc * symbols + *d as usize)
+                        as u8
+                })
+                .collect_vec();
+        };
+        let mut bits = BitVec::<u8, Msb0>::new();
+        for chunk in chunks {
+            (0..symbol_bits)
+                .rev()
+                .for_each(|i| bits.push((chunk >> i) & 1 == 1));
+        }
+        // Drop the padding of the final chunk
+        bits.truncate(bits.len() / 8 * 8);
+        bits.into_vec()
+            .into_iter()
+            .map(|byte| bit_order.arrange(byte))
+            .collect_vec()
+    }
+
+    /// Reads the length prefix, dropping the bytes past the declared length
+    fn strip_length(&self, mut data: Vec<u8>) -> Result<Vec<u8>, DecodeError> {
+        if !self.length_prefix {
+            return Ok(data);
+        }
+        let Some((prefix, _)) = data.split_first_chunk::<LENGTH_PREFIX_LEN>() else {
+            return Err(DecodeError::Truncated {
+                declared: None,
+                received: data.len(),
+            });
+        };
+        let declared = u32::from_be_bytes(*prefix) as usize;
+        data.drain(..LENGTH_PREFIX_LEN);
+        if declared > data.len() {
+            return Err(DecodeError::Truncated {
+                declared: Some(declared),
+                received: data.len(),
+            });
+        }
+        data.truncate(declared);
+        Ok(data)
+    }
+
+    /// Checks and removes the CRC of the payload, if enabled
+    fn check_crc(&self, mut data: Vec<u8>) -> Result<Vec<u8>, DecodeError> {
+        if !self.crc {
+            return Ok(data);
+        }
+        let Some(split) = data.len().checked_sub(CRC_LEN) else {
+            return Err(DecodeError::ChecksumMismatch { raw: data });
+        };
+        let crc = u32::from_be_bytes(data[split..].try_into().unwrap());
+        if CRC32.checksum(&data[..split]) != crc {
+            return Err(DecodeError::ChecksumMismatch { raw: data });
+        }
+        data.truncate(split);
+        Ok(data)
+    }
+
+    /// Undoes the error correction code, also returning the number of bits it corrected
+    fn correct_errors(&self, data: Vec<u8>) -> (Vec<u8>, usize) {
+        match self.error_correction {
+            ErrorCorrection::None => (data, 0),
+            ErrorCorrection::Hamming74 => fec::hamming74_decode(&data),
+        }
+    }
+
+    pub(crate) fn decrypt(&self, data: Vec<u8>) -> Result<Vec<u8>, DecodeError> {
+        let (data, _) = self.correct_errors(data);
+        let mut data = self.check_crc(self.strip_length(data)?)?;
+        let encrypted = if self.header {
+            let encrypted = match data.as_slice() {
+                [HEADER_MAGIC, byte, ..] if byte >> 4 == HEADER_VERSION => match byte & 0xf {
+                    PLAINTEXT => Some(false),
+                    AES_128_GCM_SIV | AES_256_GCM_SIV | CHACHA20_POLY1305 => Some(true),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let Some(encrypted) = encrypted else {
+                return Err(DecodeError::InvalidHeader { raw: data });
+            };
+            data.drain(..2);
+            encrypted
+        } else {
+            self.has_cipher()
+        };
+        if !encrypted {
+            return Ok(data);
+        }
+        #[cfg(feature = "crypto")]
+        if let Some(cipher) = &self.cipher {
+            return cipher
+                .decrypt(&data, &self.aad)
+                .map_err(|_| DecodeError::AuthFailed { raw: data });
+        }
+        Err(DecodeError::KeyRequired)
+    }
+
+    /// Decodes the samples, decrypting the payload if a cipher is set.
+    ///
+    /// If decryption fails the demodulated bytes are returned in the error, which
+    /// tells a wrong key apart from a signal too noisy to demodulate.
+    pub fn decode(&self, samples: &[f32]) -> Result<Vec<u8>, DecodeError> {
+        let samples = self.condition_signal(samples);
+        let frames = self.receive_payload(&samples);
+        let values = self.decode_frames(&frames);
+        self.frames_to_payload(self.detect_levels(&frames, values))
+            .0
+    }
+
+    /// Like [`Dosr::decode`], rejecting signals that weren't received cleanly instead of decoding
+    /// them as well as possible. Fails on an empty or silent signal, on frames with more tones
+    /// than chunks, or fewer before the final frame, and on tones off the grid.
+    pub fn decode_checked(&self, samples: &[f32]) -> Result<Vec<u8>, DecodeError> {
+        let samples = self.condition_signal(samples);
+        let mut frames = self.receive_payload(&samples);
+        // Trailing silence and partial frames are just the end of the recording
+        let len = frames
+            .iter()
+            .rposition(|frame| frame.len() == self.samples_per_frame() && !self.is_silent(frame))
+            .map_or(0, |i| i + 1);
+        frames.truncate(len);
+        if frames.is_empty() {
+            return Err(DecodeError::EmptySignal);
+        }
+        // Only the frames carrying the final data frame may have idle chunks
+        let final_frames = match self.line_coding {
+            LineCoding::None => 1,
+            LineCoding::Manchester => 2,
+        };
+        let offsets = self.pilot_offsets(&frames);
+        for (i, (frame, offset)) in frames.iter().zip(offsets).enumerate() {
+            let frequencies = self.correct_offset(self.detect_frequencies(frame), offset);
+            if let Some(frequency) = frequencies.iter().find(|f| self.chunk_band(**f).is_none()) {
+                return Err(DecodeError::OffGrid {
+                    frame: i,
+                    frequency: frequency.round() as u32,
+                });
+            }
+            let is_final = i + final_frames >= frames.len();
+            if frequencies.len() > self.chunks_per_frame
+                || (frequencies.len() < self.chunks_per_frame && !is_final)
+            {
+                return Err(DecodeError::ToneCount {
+                    frame: i,
+                    detected: frequencies.len(),
+                    expected: self.chunks_per_frame,
+                });
+            }
+        }
+        let values = self.decode_frames(&frames);
+        self.frames_to_payload(self.detect_levels(&frames, values))
+            .0
+    }
+
+    /// Like [`Dosr::decode`], checking the flag between frames to let interactive applications
+    /// abort a long decode. Once it is set, the bytes demodulated so far are returned in
+    /// [`DecodeError::Cancelled`].
+    pub fn decode_cancellable(
+        &self,
+        samples: &[f32],
+        cancel: &AtomicBool,
+    ) -> Result<Vec<u8>, DecodeError> {
+        let samples = self.condition_signal(samples);
+        let frames = self.receive_payload(&samples);
+        let mut values = Vec::with_capacity(frames.len());
+        for (frame, offset) in frames.iter().zip(self.pilot_offsets(&frames)) {
+            if cancel.load(Ordering::Relaxed) {
+                let decoded = &frames[..values.len()];
+                return Err(DecodeError::Cancelled {
+                    partial: self.frames_to_bytes(self.detect_levels(decoded, values)),
+                });
+            }
+            values.push(self.decode_frame(frame, offset));
+        }
+        self.frames_to_payload(self.detect_levels(&frames, values))
+            .0
+    }
+
+    /// Decodes the samples into a caller-provided buffer, returning the number of bytes written.
+    ///
+    /// Only the output is caller-provided, the intermediate buffers are still allocated.
+    pub fn decode_into(&self, samples: &[f32], out: &mut [u8]) -> Result<usize, DecodeError> {
+        let decoded = self.decode(samples)?;
+        let Some(out) = out.get_mut(..decoded.len()) else {
+            return Err(DecodeError::BufferTooSmall {
+                needed: decoded.len(),
+                available: out.len(),
+            });
+        };
+        out.copy_from_slice(&decoded);
+        Ok(decoded.len())
+    }
+
+    /// Decodes every transmission in a recording, each separated by at least a frame of silence,
+    /// such as those joined by [`Dosr::concat_transmissions`].
+    ///
+    /// Every transmission is decoded independently, so a corrupt one doesn't stop the others from
+    /// being recovered.
+    pub fn decode_all(&self, samples: &[f32]) -> Vec<Result<Vec<u8>, DecodeError>> {
+        self.segments(samples)
+            .into_iter()
+            // The gaps of a Morse prefix split it into segments of its own
+            .filter(|segment| {
+                self.morse_prefix.is_none() || !self.receive_payload(segment).is_empty()
+            })
+            .map(|segment| self.decode(segment))
+            .collect_vec()
+    }
+
+    /// Decodes samples encoded with [`Dosr::encode_with_training`], equalizing the payload with
+    /// the channel response measured on the training frames
+    pub fn decode_with_training(&self, samples: &[f32]) -> Result<Vec<u8>, DecodeError> {
+        let samples = self.condition_signal(samples);
+        let frames = self.receive_payload(&samples);
+        let (training, payload) = frames.split_at(TRAINING_FRAMES.min(frames.len()));
+        let equalizer = self.equalizer(training);
+        let values = payload
+            .iter()
+            .map(|frame| self.decode_equalized_frame(frame, &equalizer))
+            .collect_vec();
+        self.frames_to_payload(self.detect_levels(payload, values))
+            .0
+    }
+
+    /// Decodes the samples, also reporting per-frame metrics of the reception and whether the
+    /// recording looks cut off
+    pub fn decode_with_metrics(
+        &self,
+        samples: &[f32],
+    ) -> (Result<Vec<u8>, DecodeError>, DecodeReport) {
+        let samples = self.condition_signal(samples);
+        let received = self.receive_payload(&samples);
+        let frames = received
+            .iter()
+            .zip(self.pilot_offsets(&received))
+            .map(|(frame, offset)| self.decode_frame_with_metrics(frame, offset))
+            .collect_vec();
+        let values = frames.iter().map(|f| f.values.clone()).collect_vec();
+        let values = self.detect_levels(&received, values);
+        let corrupt_frames = self.corrupt_frames(values.clone());
+        let (_, corrected_bits) = self.correct_errors(self.frames_to_bytes(values.clone()));
+        let (payload, bit_order) = self.frames_to_payload(values);
+        let complete_frames = received
+            .iter()
+            .filter(|frame| frame.len() == self.samples_per_frame())
+            .count();
+        let truncated = received
+            .last()
+            .is_some_and(|frame| frame.len() < self.samples_per_frame() && !self.is_silent(frame));
+        let report = DecodeReport {
+            frames,
+            complete_frames,
+            truncated,
+            bit_order,
+            corrupt_frames,
+            corrected_bits,
+        };
+        (payload, report)
+    }
+
+    /// Like [`Dosr::decode_with_metrics`], for checking the quality of the reception: the SNR of
+    /// every frame, the frames with an unexpected tone count and the lowest SNR
+    pub fn decode_with_stats(
+        &self,
+        samples: &[f32],
+    ) -> (Result<Vec<u8>, DecodeError>, DecodeStats) {
+        self.decode_with_metrics(samples)
+    }
+}
+
+/// Magnitudes of the frequencies, in cycles per sample, in the samples by the Goertzel
+/// algorithm. The filters run side by side over a single pass, which keeps them independent
+/// enough to be vectorized.
+fn goertzel(samples: &[f32], frequencies: &[f32]) -> Vec<f32> {
+    let coefficients = frequencies
+        .iter()
+        .map(|f| 2.0 * (f64::consts::TAU * *f as f64).cos())
+        .collect_vec();
+    let mut s1 = vec![0.0f64; frequencies.len()];
+    let mut s2 = vec![0.0f64; frequencies.len()];
+    for x in samples {
+        for ((s1, s2), coefficient) in s1.iter_mut().zip(&mut s2).zip(&coefficients) {
+            (*s1, *s2) = (*x as f64 + coefficient * *s1 - *s2, *s1);
+        }
     }
+    s1.iter()
+        .zip(&s2)
+        .zip(&coefficients)
+        .map(|((s1, s2), coefficient)| {
+            (s1 * s1 + s2 * s2 - coefficient * s1 * s2).max(0.0).sqrt() as f32
+        })
+        .collect_vec()
 }
@@ -0,0 +1,165 @@
+#[cfg(feature = "wav")]
+use std::{mem, sync::Arc};
+
+use thiserror::Error;
+
+/// Errors that can happen while decoding a signal
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The signal was demodulated but the cipher rejected it, which means the
+    /// key, nonce or associated data don't match the ones used to encode it
+    #[error(
+        "Failed to authenticate the {} demodulated bytes, check the key and associated data",
+        raw.len()
+    )]
+    AuthFailed {
+        /// Demodulated bytes, before decryption
+        raw: Vec<u8>,
+    },
+    /// The output buffer can't hold the decoded bytes
+    #[error("Output buffer too small, {needed} bytes needed but only {available} available")]
+    BufferTooSmall { needed: usize, available: usize },
+    /// The header says the payload is encrypted but no cipher is set
+    #[error("The payload is encrypted, decoding it needs a key")]
+    KeyRequired,
+    /// The payload doesn't start with a header this version understands
+    #[error("The payload doesn't start with a valid header")]
+    InvalidHeader {
+        /// Demodulated bytes, header included
+        raw: Vec<u8>,
+    },
+    /// Some frames of a broadcast were never received, or the last one never was so the
+    /// length of the message is unknown
+    #[error(
+        "Incomplete broadcast, received {seen} distinct frames of {}",
+        total.map_or("an unknown number".to_string(), |total| total.to_string())
+    )]
+    IncompleteBroadcast { seen: usize, total: Option<usize> },
+    /// There is nothing to decode, the samples are empty or silent
+    #[error("No signal to decode, the samples are empty or silent")]
+    EmptySignal,
+    /// A frame was received with another number of tones than chunks, or fewer before the end
+    /// of the payload
+    #[error("Frame {frame} has {detected} tones, {expected} expected")]
+    ToneCount {
+        /// Index of the frame, counted from the first frame of the payload
+        frame: usize,
+        detected: usize,
+        expected: usize,
+    },
+    /// A frame was received with a tone that isn't on the tone grid
+    #[error("Frame {frame} has a tone at {frequency} Hz, off the tone grid")]
+    OffGrid {
+        /// Index of the frame, counted from the first frame of the payload
+        frame: usize,
+        /// Frequency of the tone, rounded to the hertz
+        frequency: u32,
+    },
+    /// Fewer bytes were demodulated than the length prefix declares, or too few to hold it, or
+    /// the final frame ends partway through a byte
+    #[error(
+        "Truncated message, received {received} bytes of {}",
+        declared.map_or("an unknown length".to_string(), |declared| declared.to_string())
+    )]
+    Truncated {
+        declared: Option<usize>,
+        received: usize,
+    },
+    /// The CRC of the demodulated payload doesn't match, the message is corrupt
+    #[error("The CRC of the {} demodulated bytes doesn't match", raw.len())]
+    ChecksumMismatch {
+        /// Demodulated bytes, CRC included
+        raw: Vec<u8>,
+    },
+    /// The decode was cancelled before reaching the end of the signal
+    #[error("Decoding cancelled after demodulating {} bytes", partial.len())]
+    Cancelled {
+        /// Bytes demodulated from the frames decoded before the cancellation, not decrypted
+        partial: Vec<u8>,
+    },
+    /// The WAV file to decode couldn't be read
+    #[cfg(feature = "wav")]
+    #[error(transparent)]
+    Wav(#[from] WavError),
+    /// The WAV file to decode isn't sampled at the configured rate
+    #[cfg(feature = "wav")]
+    #[error("The WAV file is sampled at {sample_rate} Hz, not {expected} Hz")]
+    WavSampleRate { sample_rate: u32, expected: u32 },
+}
+
+#[cfg(feature = "wav")]
+impl From<hound::Error> for DecodeError {
+    fn from(err: hound::Error) -> Self {
+        Self::Wav(WavError(Arc::new(err)))
+    }
+}
+
+/// Error of the WAV reader, shared so that [`DecodeError`] stays cheap to clone. Errors compare
+/// equal when they are of the same kind.
+#[cfg(feature = "wav")]
+#[derive(Debug, Clone, Error)]
+#[error(transparent)]
+pub struct WavError(pub Arc<hound::Error>);
+
+#[cfg(feature = "wav")]
+impl PartialEq for WavError {
+    fn eq(&self, other: &Self) -> bool {
+        match (&*self.0, &*other.0) {
+            (hound::Error::IoError(a), hound::Error::IoError(b)) => a.kind() == b.kind(),
+            (hound::Error::FormatError(a), hound::Error::FormatError(b)) => a == b,
+            (a, b) => mem::discriminant(a) == mem::discriminant(b),
+        }
+    }
+}
+
+#[cfg(feature = "wav")]
+impl Eq for WavError {}
+
+/// Invalid modem configurations, reported by [`Dosr::validate`](crate::Dosr::validate)
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum ConfigError {
+    #[error("Sample rate must be positive, got {0} Hz")]
+    SampleRate(f32),
+    #[error("Frame duration must be positive, got {0} s")]
+    Duration(f32),
+    #[error("Chunks must carry between 1 and 8 bits, got {0}")]
+    BitsPerChunk(usize),
+    #[error("Frames must have at least a chunk")]
+    NoChunks,
+    /// The frames are too short to hold a single sample
+    #[error("Frames of {duration_s} s at {sample_rate} Hz hold no samples")]
+    EmptyFrame { duration_s: f32, sample_rate: f32 },
+    /// The tones are closer than the frequency resolution of a frame
+    #[error(
+        "Tones {delta_freq} Hz apart can't be told apart in frames of {duration_s} s, \
+         which resolve {resolution} Hz"
+    )]
+    Unresolvable {
+        delta_freq: f32,
+        duration_s: f32,
+        resolution: f32,
+    },
+    /// The highest tone is at or above the Nyquist frequency, so it aliases down onto the grid
+    #[error(
+        "The highest tone, at {frequency} Hz, must stay below the Nyquist frequency of \
+         {nyquist} Hz"
+    )]
+    AboveNyquist { frequency: f32, nyquist: f32 },
+    /// Broadcast frames have no chunks left for data once tagged
+    #[error("Broadcast frames need more than {tag_chunks} chunks, got {chunks}")]
+    BroadcastChunks { chunks: usize, tag_chunks: usize },
+    /// The message needs more frames than the sequence tags can number
+    #[error("The message needs {frames} broadcast frames, but the tags only number {max_frames}")]
+    BroadcastTooLong { frames: usize, max_frames: usize },
+    /// The callsign has characters Morse code can't spell
+    #[error("The Morse prefix \"{0}\" can only hold letters, digits, spaces and /?.,=")]
+    MorseCallsign(String),
+    /// The Morse carrier falls on or next to the tone grid, where the decoder would take it for
+    /// a tone
+    #[error(
+        "The Morse carrier, at {0} Hz, must be at least a tone spacing away from the tone grid"
+    )]
+    MorseOnGrid(f32),
+}
@@ -0,0 +1,46 @@
+use itertools::Itertools;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::dosr::Dosr;
+
+/// Intermediate structures the encoder builds from a message, for reimplementing the modem or
+/// debugging it
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Explanation {
+    /// Modulated payload, encrypted if a cipher is set
+    pub bytes: Vec<u8>,
+    /// Chunks the payload is split into
+    pub chunks: Vec<u8>,
+    /// Chunks of every transmitted frame, after line coding
+    pub frames: Vec<Vec<u8>>,
+    /// Frequency of the tone of every chunk of every frame (Hz)
+    pub frequencies: Vec<Vec<f32>>,
+}
+
+impl Dosr {
+    /// Breaks the encoding of the data down into its intermediate steps, leaving out the
+    /// ramp-up frames
+    pub fn explain(&self, data: &[u8]) -> Explanation {
+        let bytes = self.seal(data);
+        let chunks = self.bytes_to_chunks(&bytes);
+        let frames = self.line_encode(self.chunks_to_frames(&chunks));
+        let frequencies = frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .iter()
+                    .enumerate()
+                    .map(|(chunk_idx, chunk)| self.chunk_frequency(*chunk, chunk_idx))
+                    .collect_vec()
+            })
+            .collect_vec();
+        Explanation {
+            bytes,
+            chunks,
+            frames,
+            frequencies,
+        }
+    }
+}
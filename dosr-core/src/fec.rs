@@ -0,0 +1,108 @@
+use bitvec::{order::Msb0, vec::BitVec, view::BitView};
+use itertools::Itertools;
+
+/// Forward error correction of the payload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorCorrection {
+    #[default]
+    None,
+    /// Hamming(7,4) code, correcting a flipped bit in every 7-bit codeword at the cost of 75%
+    /// more bits. The codewords are interleaved in blocks of 8, so the bits of a wrong chunk
+    /// land in different codewords and are all corrected.
+    Hamming74,
+}
+
+/// Data bytes of a full interleaving block
+const BLOCK_BYTES: usize = 4;
+/// Codewords of a full interleaving block, two per byte
+const BLOCK_CODEWORDS: usize = 2 * BLOCK_BYTES;
+/// Bits of a codeword
+const CODEWORD_BITS: usize = 7;
+
+/// Length of the encoding of a payload of the given length (bytes)
+pub(crate) fn hamming74_len(len: usize) -> usize {
+    let full = len / BLOCK_BYTES * BLOCK_CODEWORDS * CODEWORD_BITS / 8;
+    full + (2 * (len % BLOCK_BYTES) * CODEWORD_BITS).div_ceil(8)
+}
+
+pub(crate) fn hamming74_encode(data: &[u8]) -> Vec<u8> {
+    data.chunks(BLOCK_BYTES)
+        .flat_map(|block| {
+            let codewords = block
+                .iter()
+                .flat_map(|byte| [encode_nibble(byte >> 4), encode_nibble(byte & 0xf)])
+                .collect_vec();
+            interleave(&codewords).into_vec()
+        })
+        .collect_vec()
+}
+
+/// Decodes the payload, correcting a flipped bit per codeword. Also returns the number of bits
+/// corrected.
+pub(crate) fn hamming74_decode(data: &[u8]) -> (Vec<u8>, usize) {
+    let block_len = hamming74_len(BLOCK_BYTES);
+    let mut corrected = 0;
+    let bytes = data
+        .chunks(block_len)
+        .flat_map(|block| {
+            // The final block only holds whole codeword pairs, the rest is padding
+            let codewords = block.len() * 8 / CODEWORD_BITS / 2 * 2;
+            deinterleave(block, codewords)
+                .into_iter()
+                .map(|codeword| {
+                    let (nibble, fixed) = decode_codeword(codeword);
+                    corrected += fixed as usize;
+                    nibble
+                })
+                .tuples()
+                .map(|(high, low)| (high << 4) | low)
+                .collect_vec()
+        })
+        .collect_vec();
+    (bytes, corrected)
+}
+
+/// Codeword of the nibble, with the parity bits at positions 1, 2 and 4 counted from 1
+fn encode_nibble(nibble: u8) -> u8 {
+    let d = |i: u8| (nibble >> (3 - i)) & 1;
+    let p1 = d(0) ^ d(1) ^ d(3);
+    let p2 = d(0) ^ d(2) ^ d(3);
+    let p3 = d(1) ^ d(2) ^ d(3);
+    [p1, p2, d(0), p3, d(1), d(2), d(3)]
+        .iter()
+        .fold(0, |acc, bit| (acc << 1) | bit)
+}
+
+/// Nibble carried by the codeword, after flipping the bit its syndrome points at. Also returns
+/// whether a bit was flipped.
+fn decode_codeword(mut codeword: u8) -> (u8, bool) {
+    let bit = |codeword: u8, position: u8| (codeword >> (CODEWORD_BITS as u8 - position)) & 1;
+    let syndrome = (1..=CODEWORD_BITS as u8)
+        .filter(|position| bit(codeword, *position) == 1)
+        .fold(0, |acc, position| acc ^ position);
+    if syndrome != 0 {
+        codeword ^= 1 << (CODEWORD_BITS as u8 - syndrome);
+    }
+    let nibble = [3, 5, 6, 7]
+        .iter()
+        .fold(0, |acc, position| (acc << 1) | bit(codeword, *position));
+    (nibble, syndrome != 0)
+}
+
+/// Sends the first bit of every codeword, then the second one of every codeword and so on
+fn interleave(codewords: &[u8]) -> BitVec<u8, Msb0> {
+    (0..CODEWORD_BITS)
+        .flat_map(|i| {
+            codewords
+                .iter()
+                .map(move |codeword| codeword.view_bits::<Msb0>()[8 - CODEWORD_BITS + i])
+        })
+        .collect()
+}
+
+fn deinterleave(block: &[u8], codewords: usize) -> Vec<u8> {
+    let bits = block.view_bits::<Msb0>();
+    (0..codewords)
+        .map(|c| (0..CODEWORD_BITS).fold(0, |acc, i| (acc << 1) | bits[i * codewords + c] as u8))
+        .collect_vec()
+}
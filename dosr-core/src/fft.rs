@@ -0,0 +1,51 @@
+use std::{
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+use rustfft::{Fft, FftNum, FftPlanner};
+
+/// Forward FFTs of the frame length, planned on first use and shared by every frame of every
+/// decode
+#[derive(Default)]
+pub(crate) struct FftPlans {
+    single: OnceLock<Arc<dyn Fft<f32>>>,
+    double: OnceLock<Arc<dyn Fft<f64>>>,
+}
+
+impl fmt::Debug for FftPlans {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FftPlans")
+            .field("single", &self.single.get().map(|fft| fft.len()))
+            .field("double", &self.double.get().map(|fft| fft.len()))
+            .finish()
+    }
+}
+
+/// Sample types with a cached plan
+pub(crate) trait Planned: FftNum {
+    fn plan(plans: &FftPlans) -> &OnceLock<Arc<dyn Fft<Self>>>;
+}
+
+impl Planned for f32 {
+    fn plan(plans: &FftPlans) -> &OnceLock<Arc<dyn Fft<f32>>> {
+        &plans.single
+    }
+}
+
+impl Planned for f64 {
+    fn plan(plans: &FftPlans) -> &OnceLock<Arc<dyn Fft<f64>>> {
+        &plans.double
+    }
+}
+
+impl FftPlans {
+    /// Forward FFT of the given length, the cached one if it's the frame length
+    pub(crate) fn get<T: Planned>(&self, len: usize, frame_len: usize) -> Arc<dyn Fft<T>> {
+        let plan = |len| FftPlanner::<T>::new().plan_fft_forward(len);
+        if len != frame_len {
+            return plan(len);
+        }
+        T::plan(self).get_or_init(|| plan(len)).clone()
+    }
+}
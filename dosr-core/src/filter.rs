@@ -0,0 +1,56 @@
+use std::f32::consts::TAU;
+
+/// First-order pre-emphasis, boosting high frequencies: `y[n] = x[n] - alpha * x[n - 1]`
+pub fn preemphasis(samples: &mut [f32], alpha: f32) {
+    let mut previous = 0.0;
+    for s in samples.iter_mut() {
+        let x = *s;
+        *s = x - alpha * previous;
+        previous = x;
+    }
+}
+
+/// Inverse of [`preemphasis`]: `y[n] = x[n] + alpha * y[n - 1]`
+pub fn deemphasis(samples: &mut [f32], alpha: f32) {
+    let mut previous = 0.0;
+    for s in samples.iter_mut() {
+        *s += alpha * previous;
+        previous = *s;
+    }
+}
+
+/// First-order DC blocker, a high-pass filter removing any constant offset:
+/// `y[n] = x[n] - x[n - 1] + pole * y[n - 1]`
+pub fn dc_block(samples: &mut [f32], pole: f32) {
+    let (mut previous_x, mut previous_y) = (0.0, 0.0);
+    for s in samples.iter_mut() {
+        let x = *s;
+        *s = x - previous_x + pole * previous_y;
+        (previous_x, previous_y) = (x, *s);
+    }
+}
+
+/// Second-order IIR bandpass filter passing `low` to `high` Hz, a biquad with unit gain at their
+/// geometric mean
+pub fn bandpass(samples: &mut [f32], low: f32, high: f32, sample_rate: f32) {
+    let center = (low * high).sqrt();
+    let q = center / (high - low);
+    let omega = TAU * center / sample_rate;
+    let alpha = omega.sin() / (2.0 * q);
+    let a0 = 1.0 + alpha;
+    let (b0, a1, a2) = (alpha / a0, -2.0 * omega.cos() / a0, (1.0 - alpha) / a0);
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+    for s in samples.iter_mut() {
+        let x = *s;
+        *s = b0 * (x - x2) - a1 * y1 - a2 * y2;
+        (x1, x2, y1, y2) = (x, x1, *s, y1);
+    }
+}
+
+/// Scales the samples down so that their peak is at full scale, if it's beyond it
+pub fn limit(samples: &mut [f32]) {
+    let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+    if peak > 1.0 {
+        samples.iter_mut().for_each(|s| *s /= peak);
+    }
+}
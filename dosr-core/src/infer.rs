@@ -0,0 +1,299 @@
+use std::f32;
+
+use itertools::Itertools;
+use rustfft::{FftPlanner, num_complex::Complex};
+
+use crate::{DEFAULT_DELTA_FREQ, band::Band, dosr::Dosr};
+
+/// Length of the short-time windows used to find the frame boundaries (samples)
+const FLUX_WINDOW: usize = 1024;
+/// Hop between the short-time windows used to find the frame boundaries (samples)
+const FLUX_HOP: usize = 64;
+/// Zero-padding factor of the per-frame spectra used to locate the tones
+const PADDING: usize = 4;
+
+impl Dosr {
+    /// Makes a best guess of the configuration a recording was encoded with.
+    ///
+    /// The heuristics are:
+    /// - Frame duration: the spectrum changes at every frame boundary, so the autocorrelation of
+    ///   the spectral flux peaks at the frame duration, which is then rounded to the millisecond.
+    /// - Chunks per frame: the most common number of tones detected in a frame.
+    /// - Base frequency and delta: those of a band preset if every detected tone fits its grid,
+    ///   the lowest detected tone and the largest spacing every tone is a multiple of otherwise.
+    ///
+    /// They need a clean recording several frames long, with enough variety in the data for the
+    /// frame boundaries and the tone spacing to show. The base frequency is off whenever the
+    /// lowest value of the first chunk was never transmitted, and options such as line coding or
+    /// encryption can't be inferred at all.
+    pub fn infer_from_samples(samples: &[f32], sample_rate: f32) -> Option<Dosr> {
+        let samples = trim_silence(samples);
+        let flux = spectral_flux(samples);
+        let duration_s = infer_frame_duration(&flux, sample_rate)?;
+        let samples_per_frame = (duration_s * sample_rate) as usize;
+        let offset = infer_frame_offset(&flux, samples_per_frame);
+
+        let frames = samples[offset..]
+            .chunks_exact(samples_per_frame)
+            .map(|frame| frame_tones(frame, sample_rate))
+            .filter(|tones| !tones.is_empty())
+            .collect_vec();
+        let chunks_per_frame = frames
+            .iter()
+            .map(|tones| tones.len())
+            .counts()
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(tones, _)| tones)?;
+
+        let tolerance = sample_rate / samples_per_frame as f32 / 2.0;
+        let tones = cluster(frames.into_iter().flatten().collect_vec(), tolerance);
+        let (lowest, highest) = (*tones.first()?, *tones.last()?);
+        // Prefer the grid of a band preset when every tone fits it
+        let preset = [Band::Ultrasonic, Band::NearUltrasonic, Band::Audible]
+            .into_iter()
+            .find(|band| {
+                band.base_freq() <= lowest + tolerance
+                    && tones
+                        .iter()
+                        .all(|&t| fits_grid(t, band.base_freq(), band.delta_freq(), tolerance))
+            });
+        let (base_freq, delta_freq) = match preset {
+            Some(band) => (band.base_freq(), band.delta_freq()),
+            None => (lowest, infer_delta(&tones, tolerance)),
+        };
+
+        let span = ((highest - base_freq) / delta_freq).round() as usize + 1;
+        let values_per_chunk = span.div_ceil(chunks_per_frame).next_power_of_two();
+        let bits_per_chunk = (values_per_chunk.trailing_zeros() as usize).clamp(1, 8);
+
+        Dosr::try_new(
+            base_freq,
+            delta_freq,
+            bits_per_chunk,
+            chunks_per_frame,
+            duration_s,
+            sample_rate,
+        )
+        .ok()
+    }
+}
+
+fn magnitudes(samples: &[f32], fft_len: usize, planner: &mut FftPlanner<f32>) -> Vec<f32> {
+    let fft = planner.plan_fft_forward(fft_len);
+    let mut buffer = samples
+        .iter()
+        .map(|s| Complex::new(*s, 0.0))
+        .pad_using(fft_len, |_| Complex::new(0.0, 0.0))
+        .collect_vec();
+    fft.process(&mut buffer);
+    buffer
+        .iter()
+        .take(fft_len / 2)
+        .map(|c| c.norm())
+        .collect_vec()
+}
+
+/// Change of the short-time spectrum over the signal, peaking at frame boundaries
+fn spectral_flux(samples: &[f32]) -> Vec<f32> {
+    let mut planner = FftPlanner::new();
+    let hann = (0..FLUX_WINDOW)
+        .map(|i| 0.5 - 0.5 * (2.0 * f32::consts::PI * i as f32 / FLUX_WINDOW as f32).cos())
+        .collect_vec();
+    let spectra = samples
+        .windows(FLUX_WINDOW)
+        .step_by(FLUX_HOP)
+        .map(|window| {
+            let window = window.iter().zip(&hann).map(|(s, w)| s * w).collect_vec();
+            magnitudes(&window, FLUX_WINDOW, &mut planner)
+        })
+        .collect_vec();
+    spectra
+        .iter()
+        .tuple_windows()
+        .map(|(previous, current)| {
+            let energy = current.iter().sum::<f32>().max(f32::EPSILON);
+            previous
+                .iter()
+                .zip(current)
+                .map(|(p, c)| (c - p).abs())
+                .sum::<f32>()
+                / energy
+        })
+        .collect_vec()
+}
+
+/// Strips the leading and trailing silence, which would dominate the flux statistics
+fn trim_silence(samples: &[f32]) -> &[f32] {
+    let peak = samples
+        .iter()
+        .map(|s| s.abs())
+        .filter(|s| s.is_finite())
+        .fold(0.0f32, f32::max);
+    let is_signal = |s: &f32| s.abs() > 1e-3 * peak;
+    let start = samples.iter().position(is_signal).unwrap_or(0);
+    let end = samples
+        .iter()
+        .rposition(is_signal)
+        .map_or(start, |end| end + 1);
+    &samples[start..end]
+}
+
+fn infer_frame_duration(flux: &[f32], sample_rate: f32) -> Option<f32> {
+    let mean = flux.iter().sum::<f32>() / flux.len() as f32;
+    let flux = flux.iter().map(|f| f - mean).collect_vec();
+
+    // Overlapping windows correlate the flux over a window, and frames shorter than 10 ms
+    // can't hold resolvable tones anyway
+    let min_lag = ((0.01 * sample_rate) as usize / FLUX_HOP).max(FLUX_WINDOW / FLUX_HOP);
+    let max_lag = flux.len() / 2;
+    if min_lag + 2 > max_lag {
+        return None;
+    }
+    let autocorrelation = (0..=max_lag)
+        .map(|lag| {
+            flux.iter()
+                .zip(&flux[lag..])
+                .map(|(a, b)| a * b)
+                .sum::<f32>()
+                / (flux.len() - lag) as f32
+        })
+        .collect_vec();
+
+    // Skip what remains of the lobe around lag 0
+    let start = (min_lag..max_lag)
+        .find(|&lag| autocorrelation[lag] <= autocorrelation[lag + 1])
+        .unwrap_or(min_lag);
+    // The first strong peak is the frame duration, the others are its multiples
+    let max = autocorrelation[start..]
+        .iter()
+        .cloned()
+        .fold(0.0f32, f32::max);
+    if max <= 0.0 {
+        return None;
+    }
+    let lag = (start..max_lag).find(|&lag| {
+        let r = autocorrelation[lag];
+        r >= 0.5 * max && r >= autocorrelation[lag - 1] && r >= autocorrelation[lag + 1]
+    })?;
+
+    // Parabolic interpolation between the neighbouring lags
+    let (a, b, c) = (
+        autocorrelation[lag - 1],
+        autocorrelation[lag],
+        autocorrelation[lag + 1],
+    );
+    let denominator = a - 2.0 * b + c;
+    let shift = if denominator.abs() > f32::EPSILON {
+        (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    };
+    let duration_s = (lag as f32 + shift) * FLUX_HOP as f32 / sample_rate;
+    let duration_s = (duration_s * 1000.0).round() / 1000.0;
+    (duration_s > 0.0).then_some(duration_s)
+}
+
+/// Position of the first frame boundary, from the phase at which the flux is the highest
+fn infer_frame_offset(flux: &[f32], samples_per_frame: usize) -> usize {
+    // Frames needn't last a whole number of hops, so fold the flux on the position of every
+    // window within its frame rather than on its index
+    let period = samples_per_frame.div_ceil(FLUX_HOP).max(1);
+    let mut phase_flux = vec![0.0; period];
+    for (i, f) in flux.iter().enumerate() {
+        phase_flux[i * FLUX_HOP % samples_per_frame / FLUX_HOP] += f;
+    }
+    // The flux stays high for as long as a window straddles the boundary, so look for the start
+    // of that plateau
+    let plateau = FLUX_WINDOW / FLUX_HOP;
+    let phase = (0..period)
+        .max_by(|a, b| {
+            let score = |phase: usize| {
+                (phase..phase + plateau)
+                    .map(|p| phase_flux[p % period])
+                    .sum::<f32>()
+            };
+            score(*a).total_cmp(&score(*b))
+        })
+        .unwrap_or(0);
+    (phase * FLUX_HOP + FLUX_WINDOW) % samples_per_frame
+}
+
+/// Frequencies of the tones in a frame, interpolated between bins
+fn frame_tones(frame: &[f32], sample_rate: f32) -> Vec<f32> {
+    let fft_len = frame.len() * PADDING;
+    let magnitudes = magnitudes(frame, fft_len, &mut FftPlanner::new());
+    let max = magnitudes.iter().cloned().fold(0.0f32, f32::max);
+    if max <= 0.0 {
+        return vec![];
+    }
+    let bin_width = sample_rate / fft_len as f32;
+    (1..magnitudes.len() - 1)
+        .filter(|&i| {
+            let m = magnitudes[i];
+            m > 0.4 * max && m > magnitudes[i - 1] && m >= magnitudes[i + 1]
+        })
+        .map(|i| {
+            let (a, b, c) = (magnitudes[i - 1], magnitudes[i], magnitudes[i + 1]);
+            let shift = 0.5 * (a - c) / (a - 2.0 * b + c);
+            (i as f32 + shift) * bin_width
+        })
+        .collect_vec()
+}
+
+/// Merges the frequencies closer than the tolerance, returning the sorted cluster means
+fn cluster(mut frequencies: Vec<f32>, tolerance: f32) -> Vec<f32> {
+    frequencies.sort_by(f32::total_cmp);
+    let mut clusters: Vec<Vec<f32>> = vec![];
+    for f in frequencies {
+        match clusters.last_mut() {
+            Some(cluster) if f - cluster[cluster.len() - 1] <= tolerance => cluster.push(f),
+            _ => clusters.push(vec![f]),
+        }
+    }
+    clusters
+        .iter()
+        .map(|c| c.iter().sum::<f32>() / c.len() as f32)
+        .collect_vec()
+}
+
+fn fits_grid(freq: f32, base: f32, delta: f32, tolerance: f32) -> bool {
+    let steps = (freq - base) / delta;
+    steps > -0.5 && (steps - steps.round()).abs() * delta <= tolerance
+}
+
+/// Largest spacing every tone is a multiple of
+fn infer_delta(tones: &[f32], tolerance: f32) -> f32 {
+    let Some(min_spacing) = tones
+        .iter()
+        .tuple_windows()
+        .map(|(a, b)| b - a)
+        .min_by(f32::total_cmp)
+    else {
+        return DEFAULT_DELTA_FREQ;
+    };
+    (1..=4)
+        .map(|k| refine_delta(tones, min_spacing / k as f32))
+        .find(|&delta| {
+            tones
+                .iter()
+                .all(|&t| fits_grid(t, tones[0], delta, tolerance))
+        })
+        .unwrap_or(min_spacing)
+}
+
+/// Least-squares fit of the spacing, tone by tone so that small errors of the initial guess
+/// don't add up to a whole step on the distant tones
+fn refine_delta(tones: &[f32], mut delta: f32) -> f32 {
+    let (mut num, mut den) = (0.0, 0.0);
+    for t in tones {
+        let diff = t - tones[0];
+        let steps = (diff / delta).round();
+        num += diff * steps;
+        den += steps * steps;
+        if den > 0.0 {
+            delta = num / den;
+        }
+    }
+    delta
+}
@@ -1,6 +1,61 @@
+mod agc;
+mod band;
+mod broadcast;
+mod channels;
+mod config;
+#[cfg(feature = "crypto")]
+mod crypto;
 mod dosr;
+mod error;
+mod explain;
+mod fec;
+mod fft;
+mod filter;
+mod infer;
+mod live;
+mod message;
+mod modem;
+mod morse;
+mod noise;
+mod peak;
+#[cfg(feature = "playback")]
+mod playback;
+mod preamble;
+mod report;
+mod scale;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "wav")]
+mod wav;
 
-const F0: f32 = 1875.0;
-const DF: f32 = 46.875;
+/// Spacing of the default tone grid (Hz), the bin width of a 1024-point FFT at 48 kHz
+/// (`48000 / 1024`), so every tone sits on a bin centre of such an FFT
+pub const DEFAULT_DELTA_FREQ: f32 = 46.875;
+/// Base frequency of the default tone grid (Hz), 40 grid spacings up, which keeps the whole
+/// default grid above the mains hum and speech fundamentals and below 6.4 kHz
+pub const DEFAULT_BASE_FREQ: f32 = 40.0 * DEFAULT_DELTA_FREQ;
 
-pub use dosr::Dosr;
+pub use agc::Agc;
+pub use band::Band;
+pub use channels::{downmix, extract_channel};
+pub use config::ModemConfig;
+#[cfg(feature = "crypto")]
+pub use crypto::{CipherKind, NONCE_LEN};
+pub use dosr::{BitOrder, DetectionMethod, Dosr, LineCoding, Precision};
+#[cfg(feature = "wav")]
+pub use error::WavError;
+pub use error::{ConfigError, DecodeError};
+pub use explain::Explanation;
+pub use fec::ErrorCorrection;
+pub use message::EncodedMessage;
+#[cfg(feature = "wav")]
+pub use message::WavFormat;
+pub use modem::{Modem, NullModem};
+pub use noise::add_awgn;
+pub use peak::{PeakDetector, ThresholdDetector};
+#[cfg(feature = "playback")]
+pub use playback::PlaybackError;
+pub use report::{DecodeReport, DecodeStats, FrameMetrics};
+pub use scale::Scale;
+#[cfg(feature = "wav")]
+pub use wav::read_wav;
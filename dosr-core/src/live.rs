@@ -0,0 +1,38 @@
+use std::iter;
+
+use crate::{dosr::Dosr, error::DecodeError};
+
+/// Live decoding functionality
+impl Dosr {
+    /// Decodes a live stream of samples, such as a microphone's fed through a channel, yielding
+    /// every transmission as soon as it ends.
+    ///
+    /// Transmissions end with at least a frame of silence or with the stream itself. The stream
+    /// may start anywhere, but the frames only line up with the symbols of a transmission that
+    /// starts with a preamble, see [`Dosr::with_preamble`].
+    pub fn decode_stream<'a>(
+        &'a self,
+        samples: impl IntoIterator<Item = f32> + 'a,
+    ) -> impl Iterator<Item = Result<Vec<u8>, DecodeError>> + 'a {
+        let mut samples = samples.into_iter();
+        let mut transmission = vec![];
+        let mut done = false;
+        iter::from_fn(move || {
+            while !done {
+                let frame = samples
+                    .by_ref()
+                    .take(self.samples_per_frame())
+                    .collect::<Vec<_>>();
+                done = frame.len() < self.samples_per_frame();
+                let silent = self.is_silent(&frame);
+                if !silent {
+                    transmission.extend(frame);
+                }
+                if (silent || done) && !transmission.is_empty() {
+                    return Some(self.decode(&std::mem::take(&mut transmission)));
+                }
+            }
+            None
+        })
+    }
+}
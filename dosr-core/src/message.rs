@@ -0,0 +1,94 @@
+#[cfg(feature = "wav")]
+use std::{
+    io::{Seek, Write},
+    path::Path,
+};
+
+use crate::{DecodeError, Dosr};
+
+/// Sample format of the WAV files messages are saved as
+#[cfg(feature = "wav")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WavFormat {
+    /// 32-bit float samples, as encoded
+    #[default]
+    Float32,
+    /// 16-bit integer PCM, which more recording and playback tools read. Samples beyond full
+    /// scale are clipped.
+    Int16,
+}
+
+/// Samples of an encoded message, along with the modem that encoded them so they are always
+/// decoded and saved with the matching configuration
+#[derive(Debug, Clone)]
+pub struct EncodedMessage<'a> {
+    pub samples: Vec<f32>,
+    pub config: &'a Dosr,
+}
+
+impl Dosr {
+    /// Like [`Dosr::encode_data`], keeping the configuration with the samples
+    pub fn encode(&self, data: &[u8]) -> EncodedMessage<'_> {
+        EncodedMessage {
+            samples: self.encode_data(data),
+            config: self,
+        }
+    }
+}
+
+impl EncodedMessage<'_> {
+    /// Duration of the message (seconds)
+    pub fn duration_s(&self) -> f32 {
+        self.samples.len() as f32 / self.config.sample_rate()
+    }
+
+    pub fn decode(&self) -> Result<Vec<u8>, DecodeError> {
+        self.config.decode(&self.samples)
+    }
+
+    /// Writes the samples as a mono 32-bit float WAV at the configured sample rate
+    #[cfg(feature = "wav")]
+    pub fn write_wav<W: Write + Seek>(&self, writer: W) -> hound::Result<()> {
+        self.write_wav_as(writer, WavFormat::Float32)
+    }
+
+    /// Writes the samples as a mono WAV of the given format at the configured sample rate
+    #[cfg(feature = "wav")]
+    pub fn write_wav_as<W: Write + Seek>(&self, writer: W, format: WavFormat) -> hound::Result<()> {
+        let (bits_per_sample, sample_format) = match format {
+            WavFormat::Float32 => (32, hound::SampleFormat::Float),
+            WavFormat::Int16 => (16, hound::SampleFormat::Int),
+        };
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: self.config.sample_rate() as u32,
+            bits_per_sample,
+            sample_format,
+        };
+        let mut writer = hound::WavWriter::new(writer, spec)?;
+        for s in &self.samples {
+            match format {
+                WavFormat::Float32 => writer.write_sample(*s)?,
+                WavFormat::Int16 => {
+                    writer.write_sample((s.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)?
+                }
+            }
+        }
+        writer.finalize()
+    }
+
+    /// Saves the samples to a WAV file, see [`EncodedMessage::write_wav`]
+    #[cfg(feature = "wav")]
+    pub fn save(&self, path: impl AsRef<Path>) -> hound::Result<()> {
+        self.save_as(path, WavFormat::Float32)
+    }
+
+    /// Saves the samples to a WAV file of the given format, see [`EncodedMessage::write_wav_as`]
+    #[cfg(feature = "wav")]
+    pub fn save_as(&self, path: impl AsRef<Path>, format: WavFormat) -> hound::Result<()> {
+        self.write_wav_as(
+            std::io::BufWriter::new(std::fs::File::create(path)?),
+            format,
+        )
+    }
+}
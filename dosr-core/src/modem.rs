@@ -0,0 +1,93 @@
+use itertools::Itertools;
+
+use crate::{DecodeError, Dosr};
+
+/// Physical layer carrying the chunk values of every frame as samples
+///
+/// [`Dosr`] itself is the tone modem. [`NullModem`] carries the values losslessly instead, to
+/// test the layers above it (header, per-frame parity, line coding, encryption) without any
+/// detection noise, through [`Dosr::encode_with`] and [`Dosr::decode_with`].
+pub trait Modem {
+    /// Number of samples of every frame
+    fn samples_per_frame(&self) -> usize;
+
+    /// Samples of a frame carrying the chunk values
+    fn encode_frame(&self, frame: &[u8]) -> Vec<f32>;
+
+    /// Chunk values carried by the samples of a frame, without the trailing idle chunks
+    fn decode_frame(&self, samples: &[f32]) -> Vec<u8>;
+}
+
+impl Modem for Dosr {
+    fn samples_per_frame(&self) -> usize {
+        Dosr::samples_per_frame(self)
+    }
+
+    fn encode_frame(&self, frame: &[u8]) -> Vec<f32> {
+        Dosr::encode_frame(self, frame.to_vec())
+    }
+
+    fn decode_frame(&self, samples: &[f32]) -> Vec<u8> {
+        let frames = [samples.to_vec()];
+        self.detect_levels(&frames, self.decode_frames(&frames))
+            .concat()
+    }
+}
+
+/// Modem skipping the tone generation and detection, one sample per chunk holding its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullModem {
+    chunks_per_frame: usize,
+}
+
+/// Sample of a chunk left idle in the final frame
+const IDLE: f32 = -1.0;
+
+impl NullModem {
+    /// Modem for frames as wide as the configuration's
+    pub fn new(config: &Dosr) -> Self {
+        Self {
+            chunks_per_frame: config.chunks_per_frame(),
+        }
+    }
+}
+
+impl Modem for NullModem {
+    fn samples_per_frame(&self) -> usize {
+        self.chunks_per_frame
+    }
+
+    fn encode_frame(&self, frame: &[u8]) -> Vec<f32> {
+        let mut samples = frame.iter().map(|v| *v as f32).collect_vec();
+        samples.resize(self.chunks_per_frame, IDLE);
+        samples
+    }
+
+    fn decode_frame(&self, samples: &[f32]) -> Vec<u8> {
+        samples
+            .iter()
+            .take_while(|s| **s >= 0.0)
+            .map(|s| s.round() as u8)
+            .collect_vec()
+    }
+}
+
+impl Dosr {
+    /// Encodes the data into back to back frames of the given modem, without the ramp-up,
+    /// the Morse prefix, any overlap or guard intervals
+    pub fn encode_with(&self, modem: &impl Modem, data: &[u8]) -> Vec<f32> {
+        self.payload_frames(&self.seal(data))
+            .iter()
+            .flat_map(|frame| modem.encode_frame(frame))
+            .collect_vec()
+    }
+
+    /// Decodes samples produced by [`Dosr::encode_with`] with the same modem
+    pub fn decode_with(&self, modem: &impl Modem, samples: &[f32]) -> Result<Vec<u8>, DecodeError> {
+        let frames = samples
+            .chunks(modem.samples_per_frame())
+            .map(|samples| modem.decode_frame(samples))
+            .collect_vec();
+        self.frames_to_payload(frames).0
+    }
+}
@@ -0,0 +1,99 @@
+/// Words per minute of the Morse prefix unless set otherwise
+pub(crate) const DEFAULT_WPM: f32 = 20.0;
+/// Carrier frequency of the Morse prefix unless set otherwise (Hz)
+pub(crate) const DEFAULT_FREQUENCY: f32 = 700.0;
+/// Length of the silence between words (dits)
+pub(crate) const WORD_GAP: usize = 7;
+
+/// Callsign keyed in Morse code ahead of the transmission
+#[derive(Debug, Clone)]
+pub(crate) struct MorsePrefix {
+    pub text: String,
+    /// Keying speed (words per minute)
+    pub wpm: f32,
+    /// Carrier frequency (Hz)
+    pub frequency: f32,
+}
+
+impl MorsePrefix {
+    /// Length of a dit (seconds), as in the PARIS standard word
+    pub fn unit_s(&self) -> f32 {
+        1.2 / self.wpm
+    }
+}
+
+/// Whether every character of the text has a Morse code
+pub(crate) fn is_encodable(text: &str) -> bool {
+    text.chars().all(|c| c == ' ' || code(c).is_some())
+}
+
+/// Key state of every dit-long unit spelling the text, up to the end of its last element
+pub(crate) fn keying(text: &str) -> Vec<bool> {
+    let mut units = vec![];
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            units.extend([false; WORD_GAP]);
+        }
+        for (j, c) in word.chars().enumerate() {
+            if j > 0 {
+                units.extend([false; 3]);
+            }
+            for (k, element) in code(c).unwrap_or_default().chars().enumerate() {
+                if k > 0 {
+                    units.push(false);
+                }
+                let len = if element == '-' { 3 } else { 1 };
+                units.extend(std::iter::repeat_n(true, len));
+            }
+        }
+    }
+    units
+}
+
+fn code(c: char) -> Option<&'static str> {
+    let code = match c.to_ascii_uppercase() {
+        'A' => ".-",
+        'B' => "-...",
+        'C' => "-.-.",
+        'D' => "-..",
+        'E' => ".",
+        'F' => "..-.",
+        'G' => "--.",
+        'H' => "....",
+        'I' => "..",
+        'J' => ".---",
+        'K' => "-.-",
+        'L' => ".-..",
+        'M' => "--",
+        'N' => "-.",
+        'O' => "---",
+        'P' => ".--.",
+        'Q' => "--.-",
+        'R' => ".-.",
+        'S' => "...",
+        'T' => "-",
+        'U' => "..-",
+        'V' => "...-",
+        'W' => ".--",
+        'X' => "-..-",
+        'Y' => "-.--",
+        'Z' => "--..",
+        '0' => "-----",
+        '1' => ".----",
+        '2' => "..---",
+        '3' => "...--",
+        '4' => "....-",
+        '5' => ".....",
+        '6' => "-....",
+        '7' => "--...",
+        '8' => "---..",
+        '9' => "----.",
+        '/' => "-..-.",
+        '?' => "..--..",
+        '.' => ".-.-.-",
+        ',' => "--..--",
+        '=' => "-...-",
+        _ => return None,
+    };
+    Some(code)
+}
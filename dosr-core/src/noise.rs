@@ -0,0 +1,34 @@
+use std::f64::consts::TAU;
+
+use rand_core::RngCore;
+
+/// Adds white Gaussian noise to the samples, at the given signal-to-noise ratio (dB) relative to
+/// their mean power. The same seeded `rng` always adds the same noise.
+///
+/// Silent or empty signals have no power to scale the noise by and are left untouched.
+pub fn add_awgn(samples: &mut [f32], snr_db: f32, rng: &mut impl RngCore) {
+    if samples.is_empty() {
+        return;
+    }
+    let power = samples.iter().map(|s| (*s as f64).powi(2)).sum::<f64>() / samples.len() as f64;
+    let sigma = (power / 10f64.powf(snr_db as f64 / 10.0)).sqrt();
+    if sigma == 0.0 {
+        return;
+    }
+    for pair in samples.chunks_mut(2) {
+        // Box-Muller transform, which yields two independent normal deviates per draw
+        let u1 = 1.0 - unit(rng);
+        let u2 = unit(rng);
+        let radius = sigma * (-2.0 * u1.ln()).sqrt();
+        let (sin, cos) = (TAU * u2).sin_cos();
+        pair[0] += (radius * cos) as f32;
+        if let Some(s) = pair.get_mut(1) {
+            *s += (radius * sin) as f32;
+        }
+    }
+}
+
+/// Uniform deviate in [0, 1) with the 53 bits of precision of an `f64`
+fn unit(rng: &mut impl RngCore) -> f64 {
+    (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
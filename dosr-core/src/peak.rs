@@ -0,0 +1,28 @@
+use std::fmt::Debug;
+
+use crate::Dosr;
+
+/// Strategy finding the tones in the spectrum of a frame
+///
+/// Plug one in with [`Dosr::with_peak_detector`] to try other detection schemes, such as a
+/// CFAR detector or a matched filter, without forking the decoder.
+pub trait PeakDetector: Debug + Send + Sync {
+    /// Returns the frequencies (Hz) of the tones found in the magnitude spectrum of a frame,
+    /// normalized to a maximum of 1, whose bins are `bin_width` Hz apart
+    fn detect(&self, magnitudes: &[f32], bin_width: f32, config: &Dosr) -> Vec<f32>;
+}
+
+/// The default detector, keeping the local maxima above the band's threshold and optionally
+/// dropping the harmonics of stronger tones
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThresholdDetector;
+
+impl PeakDetector for ThresholdDetector {
+    fn detect(&self, magnitudes: &[f32], bin_width: f32, config: &Dosr) -> Vec<f32> {
+        config
+            .detect_peaks(magnitudes, bin_width)
+            .into_iter()
+            .map(|i| i as f32 * bin_width)
+            .collect()
+    }
+}
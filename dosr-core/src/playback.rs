@@ -0,0 +1,55 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use rodio::{OutputStream, PlayError, Sink, StreamError, buffer::SamplesBuffer};
+use thiserror::Error;
+
+use crate::{Dosr, EncodedMessage};
+
+/// How often playback checks whether it was cancelled
+const CANCEL_POLL: Duration = Duration::from_millis(20);
+
+/// Errors that can happen while playing a message
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PlaybackError {
+    /// There is no output device, or it can't be opened
+    #[error("Failed to open the output device: {0}")]
+    Device(#[from] StreamError),
+    /// The output device was lost before the message could be queued
+    #[error("Failed to play on the output device: {0}")]
+    Play(#[from] PlayError),
+}
+
+/// Playback functionality
+impl Dosr {
+    /// Encodes the data and plays it on the default output device, see [`EncodedMessage::play`]
+    pub fn play_data(&self, data: &[u8], cancel: Option<&AtomicBool>) -> Result<(), PlaybackError> {
+        self.encode(data).play(cancel)
+    }
+}
+
+impl EncodedMessage<'_> {
+    /// Plays the samples on the default output device at the configured sample rate, blocking
+    /// until they have been played in full or the cancel flag is set
+    pub fn play(&self, cancel: Option<&AtomicBool>) -> Result<(), PlaybackError> {
+        let (_stream, handle) = OutputStream::try_default()?;
+        let sink = Sink::try_new(&handle)?;
+        sink.append(SamplesBuffer::new(
+            1,
+            self.config.sample_rate() as u32,
+            self.samples.clone(),
+        ));
+        while !sink.empty() {
+            if cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                sink.stop();
+                break;
+            }
+            thread::sleep(CANCEL_POLL);
+        }
+        Ok(())
+    }
+}
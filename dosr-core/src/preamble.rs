@@ -0,0 +1,60 @@
+use itertools::Itertools;
+use rustfft::{FftPlanner, num_complex::Complex};
+
+use crate::Dosr;
+
+/// Normalized correlation with the preamble above which the signal is taken to contain it
+const MIN_CORRELATION: f32 = 0.3;
+
+impl Dosr {
+    /// Index of the first sample after the preamble, where the frames start, found by
+    /// cross-correlating the samples with the preamble.
+    ///
+    /// Returns `None` without a preamble, see [`Dosr::with_preamble`], or if no stretch of the
+    /// samples resembles it.
+    pub fn find_frame_start(&self, samples: &[f32]) -> Option<usize> {
+        let preamble = self.preamble_samples();
+        if preamble.is_empty() || samples.len() < preamble.len() {
+            return None;
+        }
+        let correlation = cross_correlation(samples, &preamble);
+        // Energy of every preamble-long window, to normalize the correlation by
+        let mut energy = vec![0.0f64; samples.len() + 1];
+        for (i, s) in samples.iter().enumerate() {
+            energy[i + 1] = energy[i] + (*s as f64).powi(2);
+        }
+        let preamble_norm = preamble.iter().map(|s| s * s).sum::<f32>().sqrt();
+        (0..=samples.len() - preamble.len())
+            .filter_map(|start| {
+                let window = (energy[start + preamble.len()] - energy[start]).sqrt() as f32;
+                (window > 0.0).then(|| (start, correlation[start] / (window * preamble_norm)))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .filter(|(_, score)| *score > MIN_CORRELATION)
+            .map(|(start, _)| start + preamble.len())
+    }
+}
+
+/// Correlation of the pattern with the samples at every lag, computed in the frequency domain
+fn cross_correlation(samples: &[f32], pattern: &[f32]) -> Vec<f32> {
+    let len = (samples.len() + pattern.len()).next_power_of_two();
+    let mut planner = FftPlanner::<f32>::new();
+    let (forward, inverse) = (planner.plan_fft_forward(len), planner.plan_fft_inverse(len));
+    let spectrum = |signal: &[f32]| {
+        let mut buffer = signal.iter().map(|s| Complex::new(*s, 0.0)).collect_vec();
+        buffer.resize(len, Complex::new(0.0, 0.0));
+        forward.process(&mut buffer);
+        buffer
+    };
+    let mut product = spectrum(samples)
+        .iter()
+        .zip(spectrum(pattern))
+        .map(|(s, p)| s * p.conj())
+        .collect_vec();
+    inverse.process(&mut product);
+    product
+        .iter()
+        .take(samples.len())
+        .map(|c| c.re / len as f32)
+        .collect_vec()
+}
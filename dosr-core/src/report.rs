@@ -0,0 +1,65 @@
+use crate::BitOrder;
+
+/// Frames received with an SNR below this (dB) are flagged as low confidence
+pub const LOW_CONFIDENCE_SNR_DB: f32 = 10.0;
+
+/// Reception metrics of a single frame
+#[derive(Debug, Clone)]
+pub struct FrameMetrics {
+    /// Number of tones detected in the frame
+    pub detected: usize,
+    /// Whether the frame isn't silent but its tone count isn't the number of chunks, as with the
+    /// idle chunks of a final frame
+    pub unexpected_tones: bool,
+    /// Chunk values decoded from the detected tones
+    pub values: Vec<u8>,
+    /// Mean magnitude of the detected tones over the mean magnitude of the other bins (dB)
+    pub snr_db: f32,
+    /// Whether the tone count was unexpected or the SNR too low to trust the frame
+    pub low_confidence: bool,
+}
+
+/// Diagnostics gathered while decoding a signal
+#[derive(Debug, Clone, Default)]
+pub struct DecodeReport {
+    /// Metrics of every frame the sample count implies, including a partial final frame
+    pub frames: Vec<FrameMetrics>,
+    /// Number of frames received in full
+    pub complete_frames: usize,
+    /// Whether the signal ends partway through a frame that isn't silent, which means the
+    /// recording was cut off mid-message
+    pub truncated: bool,
+    /// Bit order the payload was read in, the other one if bit order detection had to retry
+    pub bit_order: BitOrder,
+    /// Data frames whose checksum failed with per-frame parity, counted from the first frame of
+    /// the payload after undoing the line code
+    pub corrupt_frames: Vec<usize>,
+    /// Bits the error correction code corrected, in the configured bit order
+    pub corrected_bits: usize,
+}
+
+/// Signal quality statistics of a decode, see
+/// [`Dosr::decode_with_stats`](crate::Dosr::decode_with_stats)
+pub type DecodeStats = DecodeReport;
+
+impl DecodeReport {
+    /// Number of frames flagged as low confidence
+    pub fn low_confidence_frames(&self) -> usize {
+        self.frames.iter().filter(|f| f.low_confidence).count()
+    }
+
+    /// Number of frames with another number of tones than chunks, see
+    /// [`FrameMetrics::unexpected_tones`]
+    pub fn unexpected_tone_frames(&self) -> usize {
+        self.frames.iter().filter(|f| f.unexpected_tones).count()
+    }
+
+    /// Lowest SNR of the frames that aren't silent (dB), the weakest link of the reception
+    pub fn min_snr_db(&self) -> Option<f32> {
+        self.frames
+            .iter()
+            .map(|f| f.snr_db)
+            .filter(|snr_db| snr_db.is_finite())
+            .min_by(f32::total_cmp)
+    }
+}
@@ -0,0 +1,30 @@
+/// Musical scales the tone grid can snap to, rising from the base frequency as the root note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    /// Five notes per octave, without semitones between them, so any combination sounds fine
+    MajorPentatonic,
+    MinorPentatonic,
+    /// Seven notes per octave
+    Major,
+    /// All twelve semitones of every octave
+    Chromatic,
+}
+
+impl Scale {
+    /// Semitones of every note of an octave above its root
+    fn steps(&self) -> &'static [u8] {
+        match self {
+            Scale::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Scale::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Scale::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Scale::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        }
+    }
+
+    /// Frequency (Hz) of the note the given number of notes above the root
+    pub fn frequency(&self, root: f32, note: usize) -> f32 {
+        let steps = self.steps();
+        let semitones = 12 * (note / steps.len()) + steps[note % steps.len()] as usize;
+        root * 2f32.powf(semitones as f32 / 12.0)
+    }
+}
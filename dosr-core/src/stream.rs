@@ -0,0 +1,65 @@
+use futures::{Stream, stream};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{dosr::Dosr, error::DecodeError};
+
+/// Size of a sample on the wire (bytes)
+const SAMPLE_SIZE: usize = size_of::<f32>();
+
+struct DecodeState<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    transmission: Vec<f32>,
+    done: bool,
+}
+
+/// Asynchronous decoding functionality
+impl Dosr {
+    /// Decodes a live stream of little-endian `f32` samples, yielding every transmission as soon
+    /// as it ends.
+    ///
+    /// Transmissions end with at least a frame of silence or with the stream itself, and the
+    /// stream must start on a frame boundary. A read error is treated as the end of the stream.
+    pub fn decode_async<'a, R: AsyncRead + Unpin + 'a>(
+        &'a self,
+        reader: R,
+    ) -> impl Stream<Item = Result<Vec<u8>, DecodeError>> + 'a {
+        let state = DecodeState {
+            reader,
+            buffer: vec![0; self.samples_per_frame() * SAMPLE_SIZE],
+            transmission: vec![],
+            done: false,
+        };
+        stream::unfold(state, move |mut state| async move {
+            while !state.done {
+                let filled = read_frame(&mut state.reader, &mut state.buffer).await;
+                state.done = filled < state.buffer.len();
+                let frame = state.buffer[..filled - filled % SAMPLE_SIZE]
+                    .chunks_exact(SAMPLE_SIZE)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect::<Vec<_>>();
+                let silent = self.is_silent(&frame);
+                if !silent {
+                    state.transmission.extend(frame);
+                }
+                if (silent || state.done) && !state.transmission.is_empty() {
+                    let decoded = self.decode(&std::mem::take(&mut state.transmission));
+                    return Some((decoded, state));
+                }
+            }
+            None
+        })
+    }
+}
+
+/// Reads until the buffer is full or the stream ends, returning the number of bytes read
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R, buffer: &mut [u8]) -> usize {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => filled += n,
+        }
+    }
+    filled
+}
@@ -0,0 +1,48 @@
+use std::{io, path::Path};
+
+use hound::{SampleFormat, WavReader, WavSpec};
+
+use crate::{channels::downmix, dosr::Dosr, error::DecodeError};
+
+/// Reads the samples of a WAV file as floats in [-1, 1], interleaved if it has several channels,
+/// along with its format
+pub fn read_wav<R: io::Read>(reader: R) -> hound::Result<(Vec<f32>, WavSpec)> {
+    let reader = WavReader::new(reader)?;
+    let spec = reader.spec();
+    let samples = match spec.sample_format {
+        SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>()?,
+        SampleFormat::Int => {
+            let scale = (1u32 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+    Ok((samples, spec))
+}
+
+/// WAV file functionality
+impl Dosr {
+    /// Encodes the data into a mono 32-bit float WAV file at the configured sample rate
+    pub fn encode_to_wav(&self, data: &[u8], path: &Path) -> io::Result<()> {
+        self.encode(data).save(path).map_err(|err| match err {
+            hound::Error::IoError(err) => err,
+            err => io::Error::other(err),
+        })
+    }
+
+    /// Decodes a WAV file of any sample format, mixing its channels down to mono. The file must
+    /// be sampled at the configured sample rate.
+    pub fn decode_from_wav(&self, path: &Path) -> Result<Vec<u8>, DecodeError> {
+        let file = std::fs::File::open(path).map_err(hound::Error::IoError)?;
+        let (samples, spec) = read_wav(io::BufReader::new(file))?;
+        if spec.sample_rate as f32 != self.sample_rate() {
+            return Err(DecodeError::WavSampleRate {
+                sample_rate: spec.sample_rate,
+                expected: self.sample_rate() as u32,
+            });
+        }
+        self.decode(&downmix(&samples, spec.channels as usize))
+    }
+}
@@ -0,0 +1,33 @@
+use dosr::{Agc, Dosr};
+
+const MESSAGE: &[u8] = b"fading in and out";
+
+/// The message with its level sweeping from full down to 1/200 and back, frame by frame
+fn fading(dosr: &Dosr) -> Vec<f32> {
+    let spf = dosr.samples_per_frame();
+    dosr.encode_data(MESSAGE)
+        .chunks(spf)
+        .enumerate()
+        .flat_map(|(i, frame)| {
+            let gain = [1.0, 0.1, 0.02, 0.005, 0.02, 0.1][i % 6];
+            frame.iter().map(move |s| s * gain)
+        })
+        .collect()
+}
+
+#[test]
+fn agc_decodes_a_fading_signal() {
+    let dosr = Dosr::default().with_agc(Agc::default());
+    assert_eq!(dosr.decode(&fading(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn agc_decodes_an_attenuated_signal() {
+    let dosr = Dosr::default().with_agc(Agc::default());
+    let samples = dosr
+        .encode_data(MESSAGE)
+        .iter()
+        .map(|s| s * 0.005)
+        .collect::<Vec<_>>();
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
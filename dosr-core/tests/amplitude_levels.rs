@@ -0,0 +1,24 @@
+use dosr::Dosr;
+
+/// Thirty bytes, 60 chunks of 4 bits or 48 of 5 bits
+const MESSAGE: &[u8] = b"five bits on every single tone";
+
+#[test]
+fn two_levels_round_trip() {
+    let dosr = Dosr::default().with_amplitude_levels(2);
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn two_levels_shorten_the_signal() {
+    let plain = Dosr::default();
+    let leveled = Dosr::default().with_amplitude_levels(2);
+    let spf = plain.samples_per_frame();
+
+    assert_eq!(plain.encoded_len(MESSAGE.len()), 10 * spf);
+    assert_eq!(leveled.encoded_len(MESSAGE.len()), 8 * spf);
+    assert_eq!(
+        leveled.encode_data(MESSAGE).len(),
+        leveled.encoded_len(MESSAGE.len())
+    );
+}
@@ -0,0 +1,32 @@
+use std::f32::consts::TAU;
+
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"out of band";
+
+/// Encodes the message with a 200 Hz hum louder than the tones mixed in
+fn with_hum(dosr: &Dosr) -> Vec<f32> {
+    dosr.encode_data(MESSAGE)
+        .iter()
+        .enumerate()
+        .map(|(n, s)| s + 0.5 * (TAU * 200.0 * n as f32 / dosr.sample_rate()).sin())
+        .collect()
+}
+
+#[test]
+fn hum_defeats_unfiltered_decoding() {
+    let dosr = Dosr::default();
+    assert_ne!(dosr.decode(&with_hum(&dosr)).ok(), Some(MESSAGE.to_vec()));
+}
+
+#[test]
+fn bandpass_restores_decoding() {
+    let dosr = Dosr::default().with_bandpass(true);
+    assert_eq!(dosr.decode(&with_hum(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn bandpass_passes_clean_signals() {
+    let dosr = Dosr::default().with_bandpass(true);
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
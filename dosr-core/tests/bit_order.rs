@@ -0,0 +1,33 @@
+use dosr::{BitOrder, DecodeError, Dosr};
+
+const MESSAGE: &[u8] = b"which end first?";
+
+#[test]
+fn detection_falls_back_to_lsb0() {
+    let sender = Dosr::default()
+        .with_crc(true)
+        .with_bit_order(BitOrder::Lsb0);
+    let samples = sender.encode_data(MESSAGE);
+
+    let receiver = Dosr::default().with_crc(true);
+    assert!(matches!(
+        receiver.decode(&samples),
+        Err(DecodeError::ChecksumMismatch { .. })
+    ));
+
+    let (decoded, report) = receiver
+        .with_bit_order_detection(true)
+        .decode_with_metrics(&samples);
+    assert_eq!(decoded.unwrap(), MESSAGE);
+    assert_eq!(report.bit_order, BitOrder::Lsb0);
+}
+
+#[test]
+fn detection_keeps_the_configured_order() {
+    let dosr = Dosr::default()
+        .with_crc(true)
+        .with_bit_order_detection(true);
+    let (decoded, report) = dosr.decode_with_metrics(&dosr.encode_data(MESSAGE));
+    assert_eq!(decoded.unwrap(), MESSAGE);
+    assert_eq!(report.bit_order, BitOrder::Msb0);
+}
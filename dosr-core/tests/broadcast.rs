@@ -0,0 +1,47 @@
+use dosr::{ConfigError, Dosr};
+
+const MESSAGE: &[u8] = b"tune in any time";
+
+#[test]
+fn broadcast_round_trip() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_broadcast(MESSAGE, 1).unwrap();
+    assert_eq!(dosr.decode_broadcast(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn decode_starts_mid_cycle() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_broadcast(MESSAGE, 2).unwrap();
+    let spf = dosr.samples_per_frame();
+    // Two bytes a frame, so eight frames a cycle, joined three frames into the first one and
+    // left three frames into the second
+    let joined = &samples[3 * spf..11 * spf];
+
+    assert_eq!(dosr.decode_broadcast(joined).unwrap(), MESSAGE);
+}
+
+#[test]
+fn too_long_message_is_an_error() {
+    let dosr = Dosr::default();
+    assert!(dosr.encode_broadcast(&[0; 256], 1).is_ok());
+    assert_eq!(
+        dosr.encode_broadcast(&[0; 258], 1),
+        Err(ConfigError::BroadcastTooLong {
+            frames: 129,
+            max_frames: 128
+        })
+    );
+}
+
+#[test]
+fn narrow_grid_is_an_error() {
+    let dosr = Dosr::default().with_chunks_per_frame(2);
+    assert_eq!(
+        dosr.encode_broadcast(MESSAGE, 1),
+        Err(ConfigError::BroadcastChunks {
+            chunks: 2,
+            tag_chunks: 2
+        })
+    );
+}
@@ -0,0 +1,34 @@
+use std::sync::atomic::AtomicBool;
+
+use dosr::{DecodeError, Dosr};
+
+const MESSAGE: &[u8] = b"stop me if you can";
+
+#[test]
+fn preset_flag_cancels_before_the_first_frame() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_data(MESSAGE);
+    assert_eq!(
+        dosr.decode_cancellable(&samples, &AtomicBool::new(true)),
+        Err(DecodeError::Cancelled { partial: vec![] })
+    );
+}
+
+#[test]
+fn unset_flag_decodes_like_decode() {
+    let dosr = Dosr::default().with_crc(true);
+    let samples = dosr.encode_data(MESSAGE);
+    let cancel = AtomicBool::new(false);
+    assert_eq!(
+        dosr.decode_cancellable(&samples, &cancel),
+        Ok(MESSAGE.to_vec())
+    );
+
+    // Failures match too: half the message fails its checksum
+    let truncated = &samples[..samples.len() / 2];
+    assert!(dosr.decode(truncated).is_err());
+    assert_eq!(
+        dosr.decode_cancellable(truncated, &cancel),
+        dosr.decode(truncated)
+    );
+}
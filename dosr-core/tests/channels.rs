@@ -0,0 +1,30 @@
+use dosr::{Dosr, downmix, extract_channel};
+
+const MESSAGE: &[u8] = b"left and right";
+
+/// Interleaves the mono signal into both channels, at the given gain in each
+fn stereo(mono: &[f32], gains: [f32; 2]) -> Vec<f32> {
+    mono.iter()
+        .flat_map(|s| gains.map(|gain| s * gain))
+        .collect()
+}
+
+#[test]
+fn stereo_downmix_decodes() {
+    let dosr = Dosr::default();
+    let samples = stereo(&dosr.encode_data(MESSAGE), [1.0, 1.0]);
+
+    assert_eq!(dosr.decode(&downmix(&samples, 2)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn single_channel_decodes() {
+    let dosr = Dosr::default();
+    // Only the right channel carries the signal
+    let samples = stereo(&dosr.encode_data(MESSAGE), [0.0, 1.0]);
+
+    assert_eq!(
+        dosr.decode(&extract_channel(&samples, 2, 1)).unwrap(),
+        MESSAGE
+    );
+}
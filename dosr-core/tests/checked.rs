@@ -0,0 +1,81 @@
+use std::f32::consts::TAU;
+
+use dosr::{DecodeError, Dosr};
+
+const MESSAGE: &[u8] = b"received cleanly, or not at all";
+
+/// Adds a tone at the frequency to the given frame of the signal
+fn add_tone(dosr: &Dosr, samples: &mut [f32], frame: usize, frequency: f32) {
+    let spf = dosr.samples_per_frame();
+    for (n, s) in samples[frame * spf..(frame + 1) * spf]
+        .iter_mut()
+        .enumerate()
+    {
+        *s += 0.5 * (TAU * frequency * n as f32 / dosr.sample_rate()).sin();
+    }
+}
+
+#[test]
+fn clean_signal_decodes() {
+    let dosr = Dosr::default();
+    assert_eq!(
+        dosr.decode_checked(&dosr.encode_data(MESSAGE)).unwrap(),
+        MESSAGE
+    );
+}
+
+#[test]
+fn silence_is_an_empty_signal() {
+    let dosr = Dosr::default();
+    for samples in [vec![], vec![0.0; 3 * dosr.samples_per_frame()]] {
+        assert_eq!(dosr.decode_checked(&samples), Err(DecodeError::EmptySignal));
+    }
+}
+
+#[test]
+fn extra_tone_is_a_tone_count_error() {
+    let dosr = Dosr::default();
+    let mut samples = dosr.encode_data(MESSAGE);
+    // Two more tones in the last band, at least one of them besides its own, mid-message
+    let band_top = dosr.base_freq() + 95.0 * dosr.delta_freq();
+    add_tone(&dosr, &mut samples, 2, band_top);
+    add_tone(&dosr, &mut samples, 2, band_top - 8.0 * dosr.delta_freq());
+
+    assert!(matches!(
+        dosr.decode_checked(&samples),
+        Err(DecodeError::ToneCount {
+            frame: 2,
+            expected: 6,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn tone_below_the_grid_is_off_grid() {
+    let dosr = Dosr::default();
+    let mut samples = dosr.encode_data(MESSAGE);
+    add_tone(&dosr, &mut samples, 1, 1000.0);
+
+    assert_eq!(
+        dosr.decode_checked(&samples),
+        Err(DecodeError::OffGrid {
+            frame: 1,
+            frequency: 1000
+        })
+    );
+}
+
+#[test]
+fn missing_frames_are_truncated() {
+    let dosr = Dosr::default().with_length_prefix(true);
+    let samples = dosr.encode_data(MESSAGE);
+
+    assert_eq!(
+        dosr.decode_checked(&samples[..3 * dosr.samples_per_frame()]),
+        Err(DecodeError::Truncated {
+            declared: Some(MESSAGE.len()),
+            received: 5
+        })
+    );
+}
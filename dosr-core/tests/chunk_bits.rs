@@ -0,0 +1,52 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"Multi-byte message \x00\x7f\x80\xff";
+
+fn round_trip(dosr: &Dosr) {
+    let samples = dosr.encode_data(MESSAGE);
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn two_bits_per_chunk() {
+    round_trip(&Dosr::default().with_bits_per_chunk(2));
+}
+
+#[test]
+fn four_bits_per_chunk() {
+    round_trip(
+        &Dosr::default()
+            .with_bits_per_chunk(4)
+            .with_chunks_per_frame(3),
+    );
+}
+
+/// Widths that don't divide a byte, so chunks straddle the byte boundaries
+#[test]
+fn bits_straddling_bytes() {
+    for bits in [3, 5, 6] {
+        let dosr = Dosr::default().with_bits_per_chunk(bits);
+        for len in 1..=MESSAGE.len() {
+            let samples = dosr.encode_data(&MESSAGE[..len]);
+            assert_eq!(
+                dosr.decode(&samples).unwrap(),
+                &MESSAGE[..len],
+                "{bits} bits per chunk, {len} bytes"
+            );
+        }
+    }
+}
+
+#[test]
+fn chunks_per_frame_sets_the_tones_per_frame() {
+    let dosr = Dosr::default().with_chunks_per_frame(4);
+    let frames = dosr.encode_labeled_frames(MESSAGE);
+    assert!(frames.iter().all(|(values, _)| values.len() <= 4));
+    round_trip(&dosr);
+}
+
+#[test]
+#[should_panic(expected = "Each chunk carries between 1 and 8 bits")]
+fn bits_per_chunk_beyond_a_byte_panics() {
+    Dosr::default().with_bits_per_chunk(9);
+}
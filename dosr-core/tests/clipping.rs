@@ -0,0 +1,25 @@
+use dosr::Dosr;
+
+/// Fills every chunk of every frame, 3 bytes to a frame
+const MESSAGE: &[u8] = b"full frames!";
+
+#[test]
+fn tones_adding_up_beyond_full_scale_are_limited() {
+    // Six tones at 0.5 could add up to 3
+    let dosr = Dosr::default();
+    let samples = dosr.encode_data(MESSAGE);
+
+    let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+    assert!(peak <= 1.0, "Peak of {peak}");
+    assert!(peak > 0.99, "Peak of {peak}");
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn headroom_is_kept() {
+    let dosr = Dosr::default().with_amplitude(0.1);
+    let samples = dosr.encode_data(MESSAGE);
+
+    let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+    assert!(peak <= 0.6, "Peak of {peak}");
+}
@@ -0,0 +1,125 @@
+use dosr::{ConfigError, Dosr, ModemConfig};
+
+#[test]
+fn non_positive_sample_rate_is_rejected() {
+    for sample_rate in [0.0, -44100.0, f32::NAN] {
+        let err = Dosr::try_new(3000.0, 50.0, 4, 4, 0.1, sample_rate).unwrap_err();
+        assert!(matches!(err, ConfigError::SampleRate(_)), "{err}");
+    }
+}
+
+#[test]
+fn non_positive_duration_is_rejected() {
+    for duration_s in [0.0, -0.1, f32::INFINITY] {
+        let err = Dosr::try_new(3000.0, 50.0, 4, 4, duration_s, 44100.0).unwrap_err();
+        assert!(matches!(err, ConfigError::Duration(_)), "{err}");
+    }
+}
+
+#[test]
+fn frames_without_samples_are_rejected() {
+    let err = Dosr::try_new(3000.0, 50.0, 4, 4, 1e-6, 44100.0).unwrap_err();
+    assert!(matches!(err, ConfigError::EmptyFrame { .. }), "{err}");
+}
+
+#[test]
+fn unresolvable_tones_are_rejected() {
+    // 10 ms frames resolve 100 Hz, twice the tone spacing
+    let err = Dosr::try_new(3000.0, 50.0, 4, 4, 0.01, 44100.0).unwrap_err();
+    assert!(matches!(err, ConfigError::Unresolvable { .. }), "{err}");
+}
+
+#[test]
+fn tones_above_nyquist_are_rejected() {
+    // The highest of the 64 tones is at 34.5 kHz, above the 22.05 kHz Nyquist frequency
+    let err = Dosr::try_new(3000.0, 500.0, 4, 4, 0.1, 44100.0).unwrap_err();
+    assert_eq!(
+        err,
+        ConfigError::AboveNyquist {
+            frequency: 34500.0,
+            nyquist: 22050.0
+        }
+    );
+    assert!(err.to_string().contains("34500 Hz"), "{err}");
+    assert!(err.to_string().contains("22050 Hz"), "{err}");
+}
+
+#[test]
+fn modem_config_round_trips() {
+    let dosr = Dosr::try_new(3000.0, 100.0, 3, 4, 0.05, 44100.0).unwrap();
+    let config = dosr.modem_config().unwrap();
+    assert_eq!(config.bits_per_chunk, 3);
+
+    let rebuilt = Dosr::try_from(config).unwrap();
+    assert_eq!(rebuilt.values_per_chunk(), 8);
+    assert_eq!(rebuilt.modem_config(), Some(config));
+}
+
+#[test]
+fn modem_config_is_validated() {
+    let config = ModemConfig {
+        base_freq: 3000.0,
+        delta_freq: 100.0,
+        bits_per_chunk: 12,
+        chunks_per_frame: 4,
+        duration_s: 0.05,
+        sample_rate: 44100.0,
+    };
+    assert_eq!(
+        Dosr::try_from(config).unwrap_err(),
+        ConfigError::BitsPerChunk(12)
+    );
+    let config = ModemConfig {
+        bits_per_chunk: 2,
+        chunks_per_frame: 0,
+        ..config
+    };
+    assert_eq!(Dosr::try_from(config).unwrap_err(), ConfigError::NoChunks);
+}
+
+#[test]
+fn modem_config_needs_whole_bits() {
+    assert_eq!(
+        Dosr::default().with_values_per_chunk(10).modem_config(),
+        None
+    );
+}
+
+#[test]
+fn builders_are_validated_on_demand() {
+    assert!(Dosr::default().validate().is_ok());
+    let dosr = Dosr::default().with_sample_rate(0.0);
+    assert_eq!(dosr.validate(), Err(ConfigError::SampleRate(0.0)));
+}
+
+#[test]
+#[should_panic(expected = "Frame duration must be positive")]
+fn new_panics_on_invalid_configuration() {
+    Dosr::new(3000.0, 50.0, 4, 4, 0.0, 44100.0);
+}
+
+#[test]
+fn matching_configs_agree() {
+    assert_eq!(Dosr::default().validate_against(&Dosr::default()), Ok(()));
+}
+
+#[test]
+#[cfg(feature = "crypto")]
+fn validate_against_lists_every_mismatch() {
+    let ours = Dosr::default();
+    let theirs = Dosr::default()
+        .with_base_freq(2000.0)
+        .with_chunks_per_frame(4)
+        .with_key(dosr::CipherKind::ChaCha20Poly1305, &[1; 32]);
+
+    let mismatches = ours.validate_against(&theirs).unwrap_err();
+    let names = mismatches
+        .iter()
+        .map(|mismatch| mismatch.split_once(' ').unwrap().0)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["base_freq", "chunks_per_frame", "cipher"]);
+    assert_eq!(
+        mismatches[0],
+        "base_freq is 1875 but the other expects 2000"
+    );
+}
@@ -0,0 +1,23 @@
+use dosr::{DecodeError, Dosr, NullModem};
+
+const MESSAGE: &[u8] = b"intact?";
+
+#[test]
+fn clean_round_trip_validates() {
+    let dosr = Dosr::default().with_crc(true);
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn flipped_byte_is_caught() {
+    let dosr = Dosr::default().with_crc(true);
+    let modem = NullModem::new(&dosr);
+    let mut samples = dosr.encode_with(&modem, MESSAGE);
+    // The null modem carries a sample per 4-bit chunk, so this flips two bits of the third byte
+    samples[4] = (samples[4] as u8 ^ 0b0101) as f32;
+
+    assert!(matches!(
+        dosr.decode_with(&modem, &samples),
+        Err(DecodeError::ChecksumMismatch { .. })
+    ));
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "crypto")]
+
+use aes_gcm_siv::{Aes128GcmSiv, KeyInit};
+use dosr::{CipherKind, DecodeError, Dosr, NONCE_LEN};
+
+const MESSAGE: &[u8] = b"for your ears only";
+
+fn cipher(key: u8) -> Aes128GcmSiv {
+    Aes128GcmSiv::new_from_slice(&[key; 16]).unwrap()
+}
+
+#[test]
+fn encrypted_round_trip() {
+    let dosr = Dosr::default().with_cipher(cipher(1));
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn wrong_key_is_an_error() {
+    let samples = Dosr::default().with_cipher(cipher(1)).encode_data(MESSAGE);
+    let decoded = Dosr::default().with_cipher(cipher(2)).decode(&samples);
+
+    let Err(DecodeError::AuthFailed { raw }) = decoded else {
+        panic!("Decrypted with the wrong key: {decoded:?}");
+    };
+    assert_eq!(raw.len(), NONCE_LEN + MESSAGE.len() + 16);
+}
+
+#[test]
+fn same_nonce_encodes_the_same_samples() {
+    let dosr = Dosr::default().with_cipher(cipher(1));
+    let nonce = [7; NONCE_LEN];
+    let samples = dosr.encode_data_with_nonce(MESSAGE, &nonce);
+    assert_eq!(dosr.encode_data_with_nonce(MESSAGE, &nonce), samples);
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn different_nonces_encode_different_samples() {
+    let dosr = Dosr::default().with_cipher(cipher(1));
+    assert_ne!(
+        dosr.encode_data_with_nonce(MESSAGE, &[7; NONCE_LEN]),
+        dosr.encode_data_with_nonce(MESSAGE, &[8; NONCE_LEN])
+    );
+}
+
+#[test]
+fn matching_aad_round_trips() {
+    let dosr = Dosr::default()
+        .with_cipher(cipher(1))
+        .with_aad(b"station 7");
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn different_aad_is_an_error() {
+    let samples = Dosr::default()
+        .with_cipher(cipher(1))
+        .with_aad(b"station 7")
+        .encode_data(MESSAGE);
+    for receiver in [
+        Dosr::default()
+            .with_cipher(cipher(1))
+            .with_aad(b"station 8"),
+        Dosr::default().with_cipher(cipher(1)),
+    ] {
+        assert!(matches!(
+            receiver.decode(&samples),
+            Err(DecodeError::AuthFailed { .. })
+        ));
+    }
+}
+
+#[test]
+fn payload_shorter_than_the_nonce_is_an_error() {
+    let samples = Dosr::default().encode_data(b"short");
+    let decoded = Dosr::default().with_cipher(cipher(1)).decode(&samples);
+
+    assert!(matches!(decoded, Err(DecodeError::AuthFailed { .. })));
+}
+
+const KINDS: [CipherKind; 3] = [
+    CipherKind::Aes128GcmSiv,
+    CipherKind::Aes256GcmSiv,
+    CipherKind::ChaCha20Poly1305,
+];
+
+#[test]
+fn every_cipher_round_trips() {
+    for kind in KINDS {
+        let key = vec![7; kind.key_len()];
+        for header in [false, true] {
+            let dosr = Dosr::default().with_header(header).with_key(kind, &key);
+            let decoded = dosr.decode(&dosr.encode_data(MESSAGE));
+            assert_eq!(decoded.unwrap(), MESSAGE, "{kind:?}, header {header}");
+        }
+    }
+}
+
+#[test]
+fn ciphers_dont_decrypt_each_other() {
+    let key = [7; 32];
+    let samples = Dosr::default()
+        .with_key(CipherKind::Aes256GcmSiv, &key)
+        .encode_data(MESSAGE);
+    let decoded = Dosr::default()
+        .with_key(CipherKind::ChaCha20Poly1305, &key)
+        .decode(&samples);
+
+    assert!(matches!(decoded, Err(DecodeError::AuthFailed { .. })));
+}
+
+#[test]
+#[should_panic(expected = "32 bytes long")]
+fn short_key_panics() {
+    let _ = Dosr::default().with_key(CipherKind::Aes256GcmSiv, &[7; 16]);
+}
@@ -0,0 +1,19 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"dc offset";
+
+/// Encodes the message at the CLI's default amplitude, as recorded by an ADC adding an offset
+fn with_offset(dosr: &Dosr, offset: f32) -> Vec<f32> {
+    dosr.encode_data(MESSAGE)
+        .into_iter()
+        .map(|s| s + offset)
+        .collect()
+}
+
+#[test]
+fn dc_block_restores_decoding() {
+    let dosr = Dosr::default()
+        .with_amplitude(1.0 / 6.0)
+        .with_dc_block(true);
+    assert_eq!(dosr.decode(&with_offset(&dosr, 0.3)).unwrap(), MESSAGE);
+}
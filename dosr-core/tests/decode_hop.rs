@@ -0,0 +1,41 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"late start";
+
+/// The message starting half a frame into the recording
+fn late_signal(dosr: &Dosr) -> Vec<f32> {
+    let mut samples = vec![0.0; dosr.samples_per_frame() / 2];
+    samples.extend(dosr.encode_data(MESSAGE));
+    samples
+}
+
+#[test]
+fn defaults_to_the_hop() {
+    let dosr = Dosr::default();
+    assert_eq!(dosr.decode_hop(), dosr.samples_per_frame());
+    assert_eq!(dosr.with_decode_hop(1200).decode_hop(), 1200);
+}
+
+#[test]
+fn frame_hop_straddles_the_symbols_of_a_late_start() {
+    let dosr = Dosr::default();
+    let samples = late_signal(&dosr);
+    assert_ne!(dosr.decode(&samples).unwrap_or_default(), MESSAGE);
+    let symbols = dosr.detected_frequency_series(&dosr.encode_data(MESSAGE));
+    assert_ne!(dosr.detected_frequency_series(&samples), symbols);
+}
+
+#[test]
+fn half_frame_hop_finds_the_symbols_of_a_late_start() {
+    let dosr = Dosr::default();
+    let samples = late_signal(&dosr);
+    let symbols = dosr.detected_frequency_series(&dosr.encode_data(MESSAGE));
+    let hop = dosr.samples_per_frame() / 2;
+    let series = Dosr::default()
+        .with_decode_hop(hop)
+        .detected_frequency_series(&samples);
+    // Every other frame lines up with a symbol, the first one starting a hop in
+    let aligned = series.into_iter().skip(1).step_by(2).collect::<Vec<_>>();
+    assert_eq!(aligned, symbols);
+    assert_eq!(dosr.decode(&samples[hop..]).unwrap(), MESSAGE);
+}
@@ -0,0 +1,40 @@
+use dosr::{DecodeError, Dosr};
+
+const MESSAGE: &[u8] = b"into your buffer";
+
+#[test]
+fn writes_exactly_the_decoded_bytes() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_data(MESSAGE);
+    let mut out = [0xAA; 32];
+
+    let written = dosr.decode_into(&samples, &mut out).unwrap();
+    assert_eq!(written, MESSAGE.len());
+    assert_eq!(&out[..written], MESSAGE);
+    assert!(out[written..].iter().all(|b| *b == 0xAA));
+}
+
+#[test]
+fn buffer_of_exactly_the_length_is_enough() {
+    let dosr = Dosr::default();
+    let mut out = [0; MESSAGE.len()];
+    assert_eq!(
+        dosr.decode_into(&dosr.encode_data(MESSAGE), &mut out),
+        Ok(MESSAGE.len())
+    );
+    assert_eq!(out, MESSAGE);
+}
+
+#[test]
+fn small_buffer_is_rejected() {
+    let dosr = Dosr::default();
+    let mut out = [0; 4];
+    assert_eq!(
+        dosr.decode_into(&dosr.encode_data(MESSAGE), &mut out),
+        Err(DecodeError::BufferTooSmall {
+            needed: MESSAGE.len(),
+            available: 4
+        })
+    );
+    assert_eq!(out, [0; 4]);
+}
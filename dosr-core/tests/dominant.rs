@@ -0,0 +1,34 @@
+use std::f32::consts::TAU;
+
+use dosr::{Dosr, add_awgn};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+fn tone(dosr: &Dosr, freq: f32) -> Vec<f32> {
+    (0..dosr.samples_per_frame())
+        .map(|n| 0.5 * (TAU * freq * n as f32 / dosr.sample_rate()).sin())
+        .collect()
+}
+
+#[test]
+fn noisy_tone_is_dominant() {
+    let dosr = Dosr::default();
+    let freq = dosr.base_freq() + 7.0 * dosr.delta_freq();
+    let mut frame = tone(&dosr, freq);
+    add_awgn(&mut frame, 0.0, &mut XorShiftRng::seed_from_u64(5));
+
+    let detected = dosr.detect_dominant_frequency(&frame).unwrap();
+    let bin_width = dosr.sample_rate() / frame.len() as f32;
+    assert!((detected - freq).abs() <= bin_width, "{detected} Hz");
+}
+
+#[test]
+fn degenerate_frames_have_no_dominant_frequency() {
+    let dosr = Dosr::default();
+    let mut with_nan = tone(&dosr, dosr.base_freq());
+    with_nan[100] = f32::NAN;
+
+    assert_eq!(dosr.detect_dominant_frequency(&[]), None);
+    assert_eq!(dosr.detect_dominant_frequency(&vec![0.0; 4800]), None);
+    assert_eq!(dosr.detect_dominant_frequency(&with_nan), None);
+}
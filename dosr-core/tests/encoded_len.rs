@@ -0,0 +1,62 @@
+use dosr::{Dosr, ErrorCorrection, LineCoding};
+
+const MESSAGE: &[u8] = b"exactly as long as encoded";
+
+/// Every option changing the length of the signal, alone and combined
+fn configs() -> Vec<(&'static str, Dosr)> {
+    let configs = vec![
+        ("plain", Dosr::default()),
+        ("header", Dosr::default().with_header(true)),
+        ("length prefix", Dosr::default().with_length_prefix(true)),
+        ("crc", Dosr::default().with_crc(true)),
+        (
+            "fec",
+            Dosr::default().with_error_correction(ErrorCorrection::Hamming74),
+        ),
+        (
+            "manchester",
+            Dosr::default().with_line_coding(LineCoding::Manchester),
+        ),
+        ("ramp-up", Dosr::default().with_ramp_up(3)),
+        ("preamble", Dosr::default().with_preamble(true)),
+        ("guard", Dosr::default().with_guard_interval_s(0.01)),
+        ("overlap", Dosr::default().with_overlap(2)),
+        ("morse prefix", Dosr::default().with_morse_prefix("DOSR")),
+        (
+            "everything",
+            Dosr::default()
+                .with_header(true)
+                .with_length_prefix(true)
+                .with_crc(true)
+                .with_error_correction(ErrorCorrection::Hamming74)
+                .with_line_coding(LineCoding::Manchester)
+                .with_ramp_up(2)
+                .with_preamble(true)
+                .with_guard_interval_s(0.01)
+                .with_morse_prefix("DOSR"),
+        ),
+    ];
+    #[cfg(feature = "crypto")]
+    let configs = {
+        let mut configs = configs;
+        configs.push((
+            "cipher",
+            Dosr::default().with_key(dosr::CipherKind::ChaCha20Poly1305, &[7; 32]),
+        ));
+        configs
+    };
+    configs
+}
+
+#[test]
+fn encoded_len_matches_the_encoding() {
+    for (name, dosr) in configs() {
+        for len in [0, 1, 2, 3, 5, MESSAGE.len()] {
+            assert_eq!(
+                dosr.encoded_len(len),
+                dosr.encode_data(&MESSAGE[..len]).len(),
+                "{name}, {len} bytes"
+            );
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::f32::consts::TAU;
+
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"splatter";
+
+/// Magnitude of the frame's spectrum, zero-padded to four frames, summed over 1000 to 1500 Hz,
+/// well below the tones
+fn splatter(dosr: &Dosr) -> f32 {
+    let mut frame = dosr.encode_data_frames(MESSAGE).remove(0);
+    frame.resize(4 * frame.len(), 0.0);
+    (1000..1500)
+        .step_by(10)
+        .map(|frequency| {
+            let omega = TAU * frequency as f32 / dosr.sample_rate();
+            let (re, im) = frame
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (n, s)| {
+                    let phase = omega * n as f32;
+                    (re + s * phase.cos(), im - s * phase.sin())
+                });
+            (re * re + im * im).sqrt()
+        })
+        .sum()
+}
+
+#[test]
+fn envelope_reduces_sidelobes() {
+    let hard = splatter(&Dosr::default());
+    let enveloped = splatter(&Dosr::default().with_envelope_rolloff(0.2));
+    assert!(
+        enveloped < hard / 4.0,
+        "{enveloped} with the envelope, {hard} without"
+    );
+}
+
+#[test]
+fn enveloped_frames_round_trip() {
+    let dosr = Dosr::default().with_envelope_rolloff(0.2);
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
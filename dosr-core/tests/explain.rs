@@ -0,0 +1,27 @@
+use dosr::Dosr;
+
+#[test]
+fn explains_a_short_payload() {
+    let explanation = Dosr::default().explain(b"hi");
+    assert_eq!(explanation.bytes, b"hi");
+    // Most significant nibble first, 'h' = 0x68 and 'i' = 0x69
+    assert_eq!(explanation.chunks, [6, 8, 6, 9]);
+    // A single short final frame of four of the six chunks
+    assert_eq!(explanation.frames, [[6, 8, 6, 9]]);
+    // The value's tone in the band of each chunk, bands of 16 tones 46.875 Hz apart from 1875 Hz
+    assert_eq!(
+        explanation.frequencies,
+        [[2156.25, 3000.0, 3656.25, 4546.875]]
+    );
+}
+
+#[test]
+fn frequencies_match_the_encoded_frames() {
+    let dosr = Dosr::default();
+    let explanation = dosr.explain(b"explained payload");
+    let frames = dosr.encode_data_frames(b"explained payload");
+    assert_eq!(frames.len(), explanation.frequencies.len());
+    for (frame, frequencies) in frames.iter().zip(&explanation.frequencies) {
+        assert_eq!(&dosr.detect_frequencies_goertzel(frame), frequencies);
+    }
+}
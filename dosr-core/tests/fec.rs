@@ -0,0 +1,49 @@
+use dosr::{Dosr, ErrorCorrection, Modem};
+
+const MESSAGE: &[u8] = b"forward error correction";
+
+/// The message encoded with one chunk of its third frame changed to another tone
+fn corrupted(dosr: &Dosr) -> Vec<f32> {
+    let mut frames = dosr.encode_labeled_frames(MESSAGE);
+    let (values, samples) = &mut frames[2];
+    values[1] ^= 0b1011;
+    *samples = dosr.encode_frame(values);
+    frames
+        .into_iter()
+        .flat_map(|(_, samples)| samples)
+        .collect()
+}
+
+#[test]
+fn wrong_tone_is_corrected() {
+    let dosr = Dosr::default().with_error_correction(ErrorCorrection::Hamming74);
+    let samples = corrupted(&dosr);
+
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+    let (_, report) = dosr.decode_with_metrics(&samples);
+    assert_eq!(report.corrected_bits, 3);
+}
+
+#[test]
+fn wrong_tone_corrupts_unprotected_message() {
+    let dosr = Dosr::default();
+    assert_ne!(dosr.decode(&corrupted(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn round_trips_with_any_bits_per_chunk() {
+    for bits_per_chunk in 1..=6 {
+        let dosr = Dosr::new(1000.0, 50.0, bits_per_chunk, 2, 0.05, 48000.0)
+            .with_error_correction(ErrorCorrection::Hamming74);
+        for len in 0..=9 {
+            let message = &MESSAGE[..len];
+            let samples = dosr.encode_data(message);
+            assert_eq!(samples.len(), dosr.encoded_len(len));
+            assert_eq!(
+                dosr.decode(&samples).unwrap(),
+                message,
+                "{bits_per_chunk} bits"
+            );
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use dosr::{DetectionMethod, Dosr, add_awgn};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+const MESSAGE: &[u8] = b"tuned filters or a full spectrum";
+
+fn decoders() -> [Dosr; 2] {
+    [
+        Dosr::default(),
+        Dosr::default().with_detection_method(DetectionMethod::Goertzel),
+    ]
+}
+
+#[test]
+fn goertzel_decodes_like_the_fft() {
+    let samples = Dosr::default().encode_data(MESSAGE);
+    for dosr in decoders() {
+        assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+    }
+}
+
+#[test]
+fn goertzel_decodes_like_the_fft_in_noise() {
+    let mut samples = Dosr::default().encode_data(MESSAGE);
+    add_awgn(&mut samples, 5.0, &mut XorShiftRng::seed_from_u64(11));
+    let [fft, goertzel] = decoders().map(|dosr| dosr.decode(&samples));
+    assert_eq!(fft.unwrap(), MESSAGE);
+    assert_eq!(goertzel.unwrap(), MESSAGE);
+}
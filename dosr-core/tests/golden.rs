@@ -0,0 +1,93 @@
+//! Golden sample vectors locking down the wire format.
+//!
+//! Regenerate them after an intentional change with `cargo test -- --ignored regen_golden`.
+
+use std::{fs, path::PathBuf};
+
+use dosr::{Band, Dosr, LineCoding};
+
+/// Maximum difference tolerated between a sample and its golden value
+#[cfg(not(feature = "oscillator"))]
+const TOLERANCE: f32 = 1e-5;
+/// The golden files come from the per-sample sine, whose single precision phase drifts from the
+/// oscillator's by a few 1e-4 over a frame
+#[cfg(feature = "oscillator")]
+const TOLERANCE: f32 = 1e-3;
+
+fn cases() -> Vec<(&'static str, Dosr, &'static [u8])> {
+    vec![
+        ("default", Dosr::default().with_duration_s(0.05), b"dosr"),
+        (
+            "ultrasonic",
+            Dosr::default()
+                .with_band(Band::Ultrasonic)
+                .with_duration_s(0.05),
+            b"dosr",
+        ),
+        (
+            "manchester",
+            Dosr::default()
+                .with_line_coding(LineCoding::Manchester)
+                .with_duration_s(0.05),
+            &[0x00, 0x5a, 0xff],
+        ),
+        (
+            "custom",
+            Dosr::new(3000.0, 50.0, 2, 4, 0.05, 44100.0),
+            b"golden",
+        ),
+    ]
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.bin"))
+}
+
+fn read_golden(name: &str) -> Vec<f32> {
+    let bytes = fs::read(golden_path(name)).expect("Failed to read golden file");
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[test]
+fn encoding_matches_golden_files() {
+    for (name, dosr, message) in cases() {
+        let samples = dosr.encode_data(message);
+        let golden = read_golden(name);
+        assert_eq!(samples.len(), golden.len(), "{name}: length changed");
+        let max_error = samples
+            .iter()
+            .zip(&golden)
+            .map(|(s, g)| (s - g).abs())
+            .fold(0.0f32, f32::max);
+        assert!(
+            max_error <= TOLERANCE,
+            "{name}: samples differ by {max_error}"
+        );
+    }
+}
+
+#[test]
+fn golden_files_decode() {
+    for (name, dosr, message) in cases() {
+        assert_eq!(dosr.decode(&read_golden(name)).unwrap(), message, "{name}");
+    }
+}
+
+#[test]
+#[ignore = "overwrites the golden files"]
+fn regen_golden() {
+    for (name, dosr, message) in cases() {
+        let bytes = dosr
+            .encode_data(message)
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect::<Vec<_>>();
+        fs::write(golden_path(name), bytes).expect("Failed to write golden file");
+    }
+}
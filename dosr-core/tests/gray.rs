@@ -0,0 +1,53 @@
+use dosr::{Dosr, PeakDetector, ThresholdDetector};
+
+/// Detects the tones a tone spacing above where they are, as if every one landed a bin off
+#[derive(Debug)]
+struct OffByOne;
+
+impl PeakDetector for OffByOne {
+    fn detect(&self, magnitudes: &[f32], bin_width: f32, config: &Dosr) -> Vec<f32> {
+        ThresholdDetector
+            .detect(magnitudes, bin_width, config)
+            .into_iter()
+            .map(|frequency| frequency + config.delta_freq())
+            .collect()
+    }
+}
+
+/// Every value of a 4-bit chunk but the ones on the highest tone, 0xf in binary and 0x8 in Gray
+/// code, which would leave the band of their chunk
+const MESSAGE: &[u8] = &[0x01, 0x23, 0x45, 0x67, 0x9a, 0xbc, 0xde];
+
+fn bit_errors(dosr: Dosr) -> u32 {
+    let samples = dosr.encode_data(MESSAGE);
+    let decoded = dosr
+        .with_peak_detector(Box::new(OffByOne))
+        .decode(&samples)
+        .unwrap();
+    assert_eq!(decoded.len(), MESSAGE.len());
+    MESSAGE
+        .iter()
+        .zip(&decoded)
+        .map(|(sent, received)| (sent ^ received).count_ones())
+        .sum()
+}
+
+#[test]
+fn gray_code_flips_a_bit_per_tone_off_by_one() {
+    let binary = bit_errors(Dosr::default());
+    let gray = bit_errors(Dosr::default().with_gray_coding(true));
+
+    // A bit per chunk, two chunks per byte
+    assert_eq!(gray, 2 * MESSAGE.len() as u32);
+    assert!(
+        gray < binary,
+        "{gray} bit errors with Gray code, {binary} without"
+    );
+}
+
+#[test]
+fn gray_code_round_trips() {
+    let dosr = Dosr::default().with_gray_coding(true);
+    let samples = dosr.encode_data(MESSAGE);
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
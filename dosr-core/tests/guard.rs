@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use dosr::Dosr;
+
+/// Three bytes a frame, so eight whole frames
+const MESSAGE: &[u8] = b"twenty-four bytes long!!";
+
+fn config() -> Dosr {
+    Dosr::default().with_guard_interval_s(0.02)
+}
+
+#[test]
+fn guarded_frames_round_trip() {
+    let dosr = config();
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn guards_separate_the_frames() {
+    let dosr = config();
+    let samples = dosr.encode_data(MESSAGE);
+    let (spf, guard) = (dosr.samples_per_frame(), 960);
+
+    let frames = dosr.encode_data_frames(MESSAGE).len();
+    assert_eq!(frames, 8);
+    assert_eq!(samples.len(), frames * spf + (frames - 1) * guard);
+    assert_eq!(samples.len(), dosr.encoded_len(MESSAGE.len()));
+    for gap in samples[spf..].chunks(spf + guard) {
+        assert!(gap[..guard].iter().all(|s| *s == 0.0));
+    }
+}
+
+#[test]
+fn guards_dont_split_transmissions() {
+    let dosr = config();
+    let part = dosr.encode_data(MESSAGE);
+    let samples = dosr.concat_transmissions(&[part.clone(), part], Duration::from_secs_f32(0.2));
+
+    let decoded = dosr.decode_all(&samples);
+    assert_eq!(decoded.len(), 2);
+    assert!(decoded.into_iter().all(|d| d.unwrap() == MESSAGE));
+}
+
+#[test]
+fn no_guard_by_default() {
+    let dosr = Dosr::default();
+    let frames = dosr.encode_data_frames(MESSAGE).len();
+    assert_eq!(
+        dosr.encode_data(MESSAGE).len(),
+        frames * dosr.samples_per_frame()
+    );
+}
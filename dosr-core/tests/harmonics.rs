@@ -0,0 +1,28 @@
+use std::f32::consts::PI;
+
+use dosr::Dosr;
+
+/// Encodes the message and adds the second harmonic of every tone, as a distorting speaker would
+fn with_second_harmonics(dosr: &Dosr, message: &[u8]) -> Vec<f32> {
+    dosr.encode_labeled_frames(message)
+        .into_iter()
+        .flat_map(|(values, mut samples)| {
+            for (chunk_idx, value) in values.into_iter().enumerate() {
+                let harmonic = 2.0 * dosr.calculate_frequency(value, chunk_idx);
+                for (n, s) in samples.iter_mut().enumerate() {
+                    *s += 0.3 * (2.0 * PI * harmonic * n as f32 / dosr.sample_rate()).sin();
+                }
+            }
+            samples
+        })
+        .collect()
+}
+
+#[test]
+fn second_harmonics_are_suppressed() {
+    // The low chunks' harmonics land in the bands of the high chunks
+    let message = [0x00, 0x12, 0x34, 0x56, 0x78, 0x9a];
+    let dosr = Dosr::default().with_harmonic_suppression(true);
+    let samples = with_second_harmonics(&dosr, &message);
+    assert_eq!(dosr.decode(&samples).unwrap(), message);
+}
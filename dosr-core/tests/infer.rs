@@ -0,0 +1,60 @@
+use dosr::{Band, Dosr};
+
+/// Pseudorandom bytes, varied enough for every tone of the grid to show up
+fn message(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+fn assert_inferred(dosr: &Dosr) {
+    let samples = dosr.encode_data(&message(120));
+    let inferred = Dosr::infer_from_samples(&samples, dosr.sample_rate()).unwrap();
+
+    assert_eq!(inferred.samples_per_frame(), dosr.samples_per_frame());
+    assert_eq!(inferred.chunks_per_frame(), dosr.chunks_per_frame());
+    assert!(
+        (inferred.base_freq() - dosr.base_freq()).abs() < 1.0,
+        "base {} Hz, expected {} Hz",
+        inferred.base_freq(),
+        dosr.base_freq()
+    );
+    assert!(
+        (inferred.delta_freq() - dosr.delta_freq()).abs() < 0.5,
+        "delta {} Hz, expected {} Hz",
+        inferred.delta_freq(),
+        dosr.delta_freq()
+    );
+}
+
+#[test]
+fn infers_the_default_config() {
+    assert_inferred(&Dosr::default());
+}
+
+#[test]
+fn infers_the_ultrasonic_band() {
+    assert_inferred(&Dosr::default().with_band(Band::Ultrasonic));
+}
+
+#[test]
+fn infers_a_custom_grid() {
+    assert_inferred(&Dosr::new(1000.0, 60.0, 4, 4, 0.05, 48000.0));
+}
+
+#[test]
+fn nothing_to_infer_from() {
+    let dosr = Dosr::default();
+    let spf = dosr.samples_per_frame();
+    let samples = dosr.encode_data(&message(120));
+
+    assert!(Dosr::infer_from_samples(&[], 48000.0).is_none());
+    assert!(Dosr::infer_from_samples(&vec![0.0; 10 * spf], 48000.0).is_none());
+    assert!(Dosr::infer_from_samples(&samples[..2 * spf - 1], 48000.0).is_none());
+}
@@ -0,0 +1,39 @@
+use dosr::{DecodeError, Dosr, add_awgn};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+const MESSAGE: &[u8] = b"ten bytes!";
+
+#[test]
+fn trailing_zeros_add_no_bytes() {
+    let dosr = Dosr::default().with_length_prefix(true);
+    let mut samples = dosr.encode_data(MESSAGE);
+    samples.extend(vec![0.0; 3 * dosr.samples_per_frame() + 123]);
+
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn trailing_noise_adds_no_bytes() {
+    let dosr = Dosr::default().with_length_prefix(true);
+    let mut samples = dosr.encode_data(MESSAGE);
+    samples.extend(vec![0.0; 4 * dosr.samples_per_frame()]);
+    add_awgn(&mut samples, 10.0, &mut XorShiftRng::seed_from_u64(3));
+
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn cut_off_message_is_an_error() {
+    let dosr = Dosr::default().with_length_prefix(true);
+    let samples = dosr.encode_data(MESSAGE);
+    let cut = &samples[..samples.len() - 2 * dosr.samples_per_frame()];
+
+    assert!(matches!(
+        dosr.decode(cut),
+        Err(DecodeError::Truncated {
+            declared: Some(10),
+            ..
+        })
+    ));
+}
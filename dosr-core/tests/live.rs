@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use dosr::Dosr;
+
+#[test]
+fn stream_yields_every_transmission() {
+    let dosr = Dosr::default();
+    let samples = dosr.concat_transmissions(
+        &[dosr.encode_data(b"first"), dosr.encode_data(b"second")],
+        Duration::from_millis(300),
+    );
+    // The second transmission ends with the stream rather than with silence
+    let decoded = dosr
+        .decode_stream(samples)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded, [b"first".to_vec(), b"second".to_vec()]);
+}
+
+#[test]
+fn silent_stream_yields_nothing() {
+    let dosr = Dosr::default();
+    assert_eq!(dosr.decode_stream(vec![0.0; 48000]).count(), 0);
+}
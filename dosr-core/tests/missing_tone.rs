@@ -0,0 +1,40 @@
+use dosr::{Dosr, PeakDetector, ThresholdDetector};
+
+/// Three bytes a frame, so eight full frames
+const MESSAGE: &[u8] = b"twenty-four bytes long!!";
+
+/// Misses the tone of the third chunk of every frame
+#[derive(Debug)]
+struct MissThirdChunk;
+
+impl PeakDetector for MissThirdChunk {
+    fn detect(&self, magnitudes: &[f32], bin_width: f32, config: &Dosr) -> Vec<f32> {
+        let band = 16.0 * config.delta_freq();
+        let low = config.base_freq() + 2.0 * band - config.delta_freq() / 2.0;
+        ThresholdDetector
+            .detect(magnitudes, bin_width, config)
+            .into_iter()
+            .filter(|frequency| !(low..low + band).contains(frequency))
+            .collect()
+    }
+}
+
+#[test]
+fn missing_tone_shifts_no_other_chunk() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_data(MESSAGE);
+    let (_, clean) = dosr.decode_with_metrics(&samples);
+    let (_, missing) = dosr
+        .with_peak_detector(Box::new(MissThirdChunk))
+        .decode_with_metrics(&samples);
+
+    assert_eq!(missing.frames.len(), 8);
+    for (clean, missing) in clean.frames.iter().zip(&missing.frames) {
+        assert_eq!(missing.detected, 5);
+        assert!(missing.unexpected_tones && missing.low_confidence);
+        // The missing chunk reads as 0, the others keep their values
+        let mut expected = clean.values.clone();
+        expected[2] = 0;
+        assert_eq!(missing.values, expected);
+    }
+}
@@ -0,0 +1,29 @@
+use dosr::{ConfigError, Dosr};
+
+const MESSAGE: &[u8] = b"after the callsign";
+
+#[test]
+fn prefixed_transmission_decodes() {
+    let dosr = Dosr::default().with_morse_prefix("DE EA7XYZ");
+    let samples = dosr.encode_data(MESSAGE);
+
+    assert!(samples.len() > Dosr::default().encode_data(MESSAGE).len());
+    assert_eq!(samples.len(), dosr.encoded_len(MESSAGE.len()));
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn unspellable_callsign_is_an_error() {
+    let err = Dosr::default().try_with_morse_prefix("EA7#").unwrap_err();
+    assert_eq!(err, ConfigError::MorseCallsign("EA7#".to_string()));
+}
+
+#[test]
+fn carrier_on_the_grid_is_an_error() {
+    // The grid from 600 Hz covers the 700 Hz carrier
+    let dosr = Dosr::new(600.0, 46.875, 4, 6, 0.1, 48000.0);
+    assert_eq!(
+        dosr.try_with_morse_prefix("EA7XYZ").unwrap_err(),
+        ConfigError::MorseOnGrid(700.0)
+    );
+}
@@ -0,0 +1,27 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"non-finite samples";
+
+#[test]
+fn scattered_non_finite_samples_still_decode() {
+    let dosr = Dosr::default();
+    let mut samples = dosr.encode_data(MESSAGE);
+    let corruptions = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY];
+    for (i, sample) in samples.iter_mut().step_by(997).enumerate() {
+        *sample = corruptions[i % corruptions.len()];
+    }
+
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn all_non_finite_samples_do_not_panic() {
+    let dosr = Dosr::default();
+    let samples = vec![f32::NAN; 4 * 4800];
+
+    assert_eq!(dosr.decode(&samples).unwrap(), Vec::<u8>::new());
+
+    let (decoded, report) = dosr.decode_with_metrics(&samples);
+    assert_eq!(decoded.unwrap(), Vec::<u8>::new());
+    assert!(report.frames.iter().all(|f| f.low_confidence));
+}
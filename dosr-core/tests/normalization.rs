@@ -0,0 +1,34 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"full scale";
+
+/// Six quiet tones, whose frames peak well below full scale
+fn config() -> Dosr {
+    Dosr::default().with_amplitude(0.02)
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().map(|s| s.abs()).fold(0.0, f32::max)
+}
+
+#[test]
+fn frames_peak_at_full_scale() {
+    let frames = config()
+        .with_normalization(true)
+        .encode_data_frames(MESSAGE);
+    for frame in frames {
+        assert!((peak(&frame) - 1.0).abs() < 1e-6, "{}", peak(&frame));
+    }
+}
+
+#[test]
+fn frames_keep_their_level_without_normalization() {
+    let frames = config().encode_data_frames(MESSAGE);
+    assert!(frames.iter().all(|frame| peak(frame) < 0.2));
+}
+
+#[test]
+fn normalized_signal_round_trips() {
+    let dosr = config().with_normalization(true);
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
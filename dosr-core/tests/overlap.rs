@@ -0,0 +1,15 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"twice the symbol rate";
+
+#[test]
+fn overlapped_frames_round_trip() {
+    let dosr = Dosr::default().with_overlap(2);
+    let samples = dosr.encode_data(MESSAGE);
+
+    // Seven frames, each starting half a frame after the previous one
+    let plain = Dosr::default().encode_data(MESSAGE);
+    assert_eq!(plain.len(), 7 * dosr.samples_per_frame());
+    assert_eq!(samples.len(), 4 * dosr.samples_per_frame());
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
@@ -0,0 +1,32 @@
+use dosr::{Dosr, Modem};
+
+const MESSAGE: &[u8] = b"checksummed frames";
+
+/// The message encoded with one chunk of the given frame changed to another tone
+fn corrupted(dosr: &Dosr, frame: usize) -> Vec<f32> {
+    let mut frames = dosr.encode_labeled_frames(MESSAGE);
+    let (values, samples) = &mut frames[frame];
+    values[1] ^= 0b0110;
+    *samples = dosr.encode_frame(values);
+    frames
+        .into_iter()
+        .flat_map(|(_, samples)| samples)
+        .collect()
+}
+
+#[test]
+fn flipped_tone_flags_its_frame() {
+    let dosr = Dosr::default().with_per_frame_parity(true);
+    for frame in [0, 3, 6] {
+        let (_, report) = dosr.decode_with_metrics(&corrupted(&dosr, frame));
+        assert_eq!(report.corrupt_frames, [frame]);
+    }
+}
+
+#[test]
+fn clean_frames_pass_parity() {
+    let dosr = Dosr::default().with_per_frame_parity(true);
+    let (decoded, report) = dosr.decode_with_metrics(&dosr.encode_data(MESSAGE));
+    assert_eq!(decoded.unwrap(), MESSAGE);
+    assert!(report.corrupt_frames.is_empty());
+}
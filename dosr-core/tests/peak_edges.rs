@@ -0,0 +1,31 @@
+//! Peaks in the first and last bins of the spectrum, whose neighbours lie past its ends
+
+use std::f32::consts::TAU;
+
+use dosr::Dosr;
+
+#[test]
+fn peak_in_the_dc_bin() {
+    let dosr = Dosr::default();
+    let frame = vec![0.5; dosr.samples_per_frame()];
+
+    assert_eq!(dosr.detected_frequency_series(&frame), vec![vec![0.0]]);
+    assert_eq!(dosr.decode(&frame).unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn peak_in_the_last_bin() {
+    let dosr = Dosr::default();
+    let len = dosr.samples_per_frame();
+    let bin_width = dosr.sample_rate() / len as f32;
+    let frequency = (len / 2 - 1) as f32 * bin_width;
+    let frame = (0..len)
+        .map(|n| 0.5 * (TAU * frequency * n as f32 / dosr.sample_rate()).sin())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        dosr.detected_frequency_series(&frame),
+        vec![vec![frequency]]
+    );
+    assert_eq!(dosr.decode(&frame).unwrap(), Vec::<u8>::new());
+}
@@ -0,0 +1,42 @@
+use dosr::Dosr;
+
+/// Fills whole frames, as the tones of the idle chunks of a final partial frame stop short
+const MESSAGE: &[u8] = b"no clicks between frames";
+
+/// Largest jump between consecutive samples across a frame boundary, and within the frames
+fn jumps(dosr: &Dosr, samples: &[f32]) -> (f32, f32) {
+    let spf = dosr.samples_per_frame();
+    let (mut boundary, mut interior) = (0.0f32, 0.0f32);
+    for (n, pair) in samples.windows(2).enumerate() {
+        let jump = (pair[1] - pair[0]).abs();
+        if (n + 1) % spf == 0 {
+            boundary = boundary.max(jump);
+        } else {
+            interior = interior.max(jump);
+        }
+    }
+    (boundary, interior)
+}
+
+#[test]
+fn frame_boundaries_are_continuous() {
+    let dosr = Dosr::default().with_continuous_phase(true);
+    let samples = dosr.encode_data(MESSAGE);
+    let (boundary, interior) = jumps(&dosr, &samples);
+
+    assert!(
+        boundary <= interior + 1e-3,
+        "Jump of {boundary} at a frame boundary, at most {interior} within the frames"
+    );
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn ramp_up_is_continuous() {
+    let dosr = Dosr::default().with_ramp_up(3).with_continuous_phase(true);
+    let samples = dosr.encode_data(MESSAGE);
+    let (boundary, interior) = jumps(&dosr, &samples);
+
+    assert!(boundary <= interior + 1e-3);
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
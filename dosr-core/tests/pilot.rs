@@ -0,0 +1,80 @@
+use std::f32::consts::TAU;
+
+use dosr::Dosr;
+
+const PILOT: f32 = 1500.0;
+const PILOT_AMPLITUDE: f32 = 0.2;
+
+/// Synthesizes the message with every tone, pilot included, shifted by an offset that grows
+/// by `drift` Hz every frame, as a transmitter whose clock slowly wanders would
+fn drifting(dosr: &Dosr, message: &[u8], drift: f32) -> Vec<f32> {
+    let sample_rate = dosr.sample_rate();
+    let frames = dosr.encode_labeled_frames(message);
+    let mut phases = vec![0.0f32; frames.first().map_or(0, |f| f.0.len()) + 1];
+    let mut samples = vec![];
+    for (i, (values, frame)) in frames.iter().enumerate() {
+        let offset = i as f32 * drift;
+        let tones = values
+            .iter()
+            .enumerate()
+            .map(|(chunk_idx, value)| (dosr.calculate_frequency(*value, chunk_idx), 0.5))
+            .chain([(PILOT, PILOT_AMPLITUDE)])
+            .collect::<Vec<_>>();
+        for _ in 0..frame.len() {
+            let mut sample = 0.0;
+            for (phase, (frequency, amplitude)) in phases.iter_mut().zip(&tones) {
+                sample += amplitude * phase.sin();
+                *phase = (*phase + TAU * (frequency + offset) / sample_rate) % TAU;
+            }
+            samples.push(sample);
+        }
+    }
+    samples
+}
+
+#[test]
+fn pilot_tracks_a_drifting_offset() {
+    let message = b"the pilot follows the drift".to_vec();
+    let dosr = Dosr::default().with_pilot(PILOT, PILOT_AMPLITUDE);
+    // 10 Hz per frame adds up to over a tone spacing by the end of the message
+    let samples = drifting(&dosr, &message, 10.0);
+    assert_eq!(dosr.decode(&samples).unwrap(), message);
+
+    let without_pilot = Dosr::default();
+    assert_ne!(without_pilot.decode(&samples).unwrap(), message);
+}
+
+#[test]
+fn pilot_round_trips() {
+    let message = b"pilot".to_vec();
+    let dosr = Dosr::default().with_pilot(PILOT, PILOT_AMPLITUDE);
+    let samples = dosr.encode_data(&message);
+    assert_eq!(dosr.decode(&samples).unwrap(), message);
+}
+
+/// Resamples the signal as a recording whose sample clock runs `ratio` times the nominal rate,
+/// interpolating linearly between the samples
+fn resampled(samples: &[f32], ratio: f32) -> Vec<f32> {
+    let len = ((samples.len() - 1) as f32 * ratio) as usize;
+    (0..len)
+        .map(|n| {
+            let position = n as f32 / ratio;
+            let (i, fraction) = (position as usize, position.fract());
+            samples[i] * (1.0 - fraction) + samples[i + 1] * fraction
+        })
+        .collect()
+}
+
+#[test]
+fn clock_tracking_follows_a_drifting_sample_clock() {
+    let message = b"a long recording on a cheap sound card, whose clock runs fast".to_vec();
+    let dosr = Dosr::default().with_pilot(PILOT, PILOT_AMPLITUDE);
+    // 0.2% slips the frames by about 10 samples each, 200 by the end of the message
+    let samples = resampled(&dosr.encode_data(&message), 1.002);
+
+    let tracked = Dosr::default()
+        .with_pilot(PILOT, PILOT_AMPLITUDE)
+        .with_clock_tracking(true);
+    assert_eq!(tracked.decode(&samples).unwrap(), message);
+    assert_ne!(dosr.decode(&samples).unwrap(), message);
+}
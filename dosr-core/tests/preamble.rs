@@ -0,0 +1,46 @@
+use dosr::{Dosr, add_awgn};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+const MESSAGE: &[u8] = b"self-aligning";
+
+#[test]
+fn leading_silence_is_skipped() {
+    let dosr = Dosr::default().with_preamble(true);
+    let mut samples = vec![0.0; 1234];
+    samples.extend(dosr.encode_data(MESSAGE));
+
+    assert_eq!(
+        dosr.find_frame_start(&samples),
+        Some(1234 + dosr.samples_per_frame())
+    );
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn noisy_capture_is_aligned() {
+    let dosr = Dosr::default().with_preamble(true);
+    let mut samples = vec![0.0; 3000];
+    samples.extend(dosr.encode_data(MESSAGE));
+    add_awgn(&mut samples, 0.0, &mut XorShiftRng::seed_from_u64(1));
+
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn no_preamble_in_a_plain_transmission() {
+    let dosr = Dosr::default().with_preamble(true);
+    assert_eq!(
+        dosr.find_frame_start(&Dosr::default().encode_data(MESSAGE)),
+        None
+    );
+}
+
+#[test]
+fn encoded_len_counts_the_preamble() {
+    let dosr = Dosr::default().with_preamble(true);
+    assert_eq!(
+        dosr.encode_data(MESSAGE).len(),
+        dosr.encoded_len(MESSAGE.len())
+    );
+}
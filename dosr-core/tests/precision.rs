@@ -0,0 +1,24 @@
+use dosr::{Dosr, Precision, add_awgn};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+const MESSAGE: &[u8] = b"single or double";
+
+#[test]
+fn every_precision_round_trips() {
+    for precision in [Precision::Single, Precision::Double] {
+        let dosr = Dosr::default().with_precision(precision);
+        let decoded = dosr.decode(&dosr.encode_data(MESSAGE));
+        assert_eq!(decoded.unwrap(), MESSAGE, "{precision:?}");
+    }
+}
+
+#[test]
+fn every_precision_round_trips_through_noise() {
+    for precision in [Precision::Single, Precision::Double] {
+        let dosr = Dosr::default().with_precision(precision);
+        let mut samples = dosr.encode_data(MESSAGE);
+        add_awgn(&mut samples, 10.0, &mut XorShiftRng::seed_from_u64(3));
+        assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE, "{precision:?}");
+    }
+}
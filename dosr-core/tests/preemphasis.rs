@@ -0,0 +1,41 @@
+use std::f32::consts::TAU;
+
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"through a dull speaker";
+
+/// The message through a one-pole low-pass channel rolling off the highs, picking up an 8 kHz
+/// whine at the receiving end. Flattened by the low-pass, the tones fall under the whine that
+/// sets the normalization of every frame.
+fn dull_channel(dosr: &Dosr) -> Vec<f32> {
+    let mut lowpassed = 0.0;
+    dosr.encode_data(MESSAGE)
+        .iter()
+        .enumerate()
+        .map(|(n, s)| {
+            lowpassed = 0.5 * s + 0.5 * lowpassed;
+            lowpassed + 0.3 * (TAU * 8000.0 * n as f32 / dosr.sample_rate()).sin()
+        })
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    let dosr = Dosr::default().with_preemphasis(0.9);
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn plain_signal_is_lost_on_a_dull_channel() {
+    let dosr = Dosr::default();
+    assert_ne!(
+        dosr.decode(&dull_channel(&dosr)).unwrap_or_default(),
+        MESSAGE
+    );
+}
+
+#[test]
+fn deemphasis_cuts_the_high_frequency_interference() {
+    let dosr = Dosr::default().with_preemphasis(0.9);
+    assert_eq!(dosr.decode(&dull_channel(&dosr)).unwrap(), MESSAGE);
+}
@@ -0,0 +1,53 @@
+//! Noiseless round trips over random configurations and payloads
+
+use dosr::Dosr;
+use proptest::{prelude::*, test_runner::FileFailurePersistence};
+
+/// Valid configurations whose whole tone grid stays below the Nyquist frequency. Tones are at
+/// least two bins apart, since the peaks of tones in adjacent bins merge.
+fn config() -> impl Strategy<Value = Dosr> {
+    (
+        prop_oneof![Just(8000.0f32), Just(16000.0), Just(44100.0), Just(48000.0)],
+        0.02f32..0.2,
+        2usize..=16,
+        1usize..=8,
+        2.0f32..4.0,
+        2usize..=20,
+    )
+        .prop_filter_map(
+            "tone grid above the Nyquist frequency",
+            |(sample_rate, duration_s, values, chunks, spacing, base_bins)| {
+                // Spacing and base frequency in multiples of the frequency resolution
+                let resolution = 1.0 / duration_s;
+                let dosr = Dosr::try_new(
+                    base_bins as f32 * resolution,
+                    spacing * resolution,
+                    1,
+                    chunks,
+                    duration_s,
+                    sample_rate,
+                )
+                .ok()?
+                .with_values_per_chunk(values);
+                let highest = dosr.calculate_frequency(values as u8 - 1, chunks - 1);
+                (highest < 0.45 * sample_rate).then_some(dosr)
+            },
+        )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 64,
+        failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn noiseless_round_trip(
+        dosr in config(),
+        payload in prop::collection::vec(any::<u8>(), 0..48),
+    ) {
+        let samples = dosr.encode_data(&payload);
+        prop_assert_eq!(dosr.decode(&samples).unwrap(), payload);
+    }
+}
@@ -0,0 +1,56 @@
+use dosr::{ConfigError, Dosr, Scale};
+
+const MESSAGE: &[u8] = b"a little tune";
+
+/// 16 tones, four chunks of four notes each, fitting in a few octaves of a scale
+fn melodic(scale: Scale) -> Dosr {
+    Dosr::new(220.0, 10.0, 2, 4, 0.1, 48000.0).with_scale(scale)
+}
+
+#[test]
+fn notes_rise_an_octave_per_scale() {
+    assert_eq!(Scale::Chromatic.frequency(440.0, 0), 440.0);
+    assert_eq!(Scale::Chromatic.frequency(440.0, 12), 880.0);
+    assert_eq!(Scale::MajorPentatonic.frequency(440.0, 5), 880.0);
+    assert_eq!(Scale::Major.frequency(440.0, 14), 1760.0);
+    let fifth = Scale::MinorPentatonic.frequency(440.0, 3);
+    assert!((fifth - 440.0 * 1.5).abs() < 1.0, "{fifth} Hz");
+}
+
+#[test]
+fn scales_round_trip() {
+    for scale in [
+        Scale::MajorPentatonic,
+        Scale::MinorPentatonic,
+        Scale::Major,
+        Scale::Chromatic,
+    ] {
+        let dosr = melodic(scale);
+        assert_eq!(dosr.validate(), Ok(()), "{scale:?}");
+        assert_eq!(
+            dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(),
+            MESSAGE,
+            "{scale:?}"
+        );
+    }
+}
+
+#[test]
+fn crowded_low_notes_are_unresolvable() {
+    // A semitone above 100 Hz is 6 Hz, finer than the 10 Hz bins of 0.1 s frames
+    let dosr = Dosr::new(100.0, 10.0, 2, 4, 0.1, 48000.0).with_scale(Scale::Chromatic);
+    assert!(matches!(
+        dosr.validate(),
+        Err(ConfigError::Unresolvable { .. })
+    ));
+}
+
+#[test]
+fn scale_beyond_nyquist_is_rejected() {
+    // 96 notes of a pentatonic scale span over 19 octaves
+    let dosr = Dosr::default().with_scale(Scale::MajorPentatonic);
+    assert!(matches!(
+        dosr.validate(),
+        Err(ConfigError::AboveNyquist { .. })
+    ));
+}
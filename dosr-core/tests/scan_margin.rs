@@ -0,0 +1,37 @@
+use std::f32::consts::TAU;
+
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"above the grid";
+
+/// Six tones at 0.15 under a whine at 0.5 some 670 Hz above the highest tone of the grid,
+/// 6328.125 Hz
+fn whining_signal(dosr: &Dosr) -> Vec<f32> {
+    dosr.encode_data(MESSAGE)
+        .iter()
+        .enumerate()
+        .map(|(n, s)| s + 0.5 * (TAU * 7000.0 * n as f32 / dosr.sample_rate()).sin())
+        .collect()
+}
+
+fn config() -> Dosr {
+    Dosr::default().with_amplitude(0.15)
+}
+
+#[test]
+fn full_spectrum_normalizes_to_the_whine() {
+    let dosr = config();
+    assert_ne!(dosr.decode(&whining_signal(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn narrow_margin_skips_the_whine() {
+    let dosr = config().with_scan_margin(200.0);
+    assert_eq!(dosr.decode(&whining_signal(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn wide_margin_scans_the_whine() {
+    let dosr = config().with_scan_margin(1000.0);
+    assert_ne!(dosr.decode(&whining_signal(&dosr)).unwrap(), MESSAGE);
+}
@@ -0,0 +1,93 @@
+use dosr::{DecodeError, Dosr, LineCoding, PeakDetector, ThresholdDetector};
+
+/// Messages of every length up to a few frames, so the final frame takes every possible width
+fn messages() -> impl Iterator<Item = Vec<u8>> {
+    (1..=12u8).map(|len| (0..len).map(|i| i.wrapping_mul(37) ^ 0x5a).collect())
+}
+
+#[test]
+fn messages_with_a_short_final_frame_decode() {
+    let configs = [
+        Dosr::default(),
+        Dosr::new(3000.0, 50.0, 2, 5, 0.05, 44100.0),
+        Dosr::default().with_line_coding(LineCoding::Manchester),
+    ];
+    for dosr in configs {
+        for message in messages() {
+            let samples = dosr.encode_data(&message);
+            assert_eq!(dosr.decode(&samples).unwrap(), message, "{dosr:?}");
+        }
+    }
+}
+
+#[test]
+fn short_final_frame_has_idle_chunks() {
+    let dosr = Dosr::default();
+    // 4 bytes are 8 chunks: a full frame of 6 and a final one of 2
+    let frames = dosr.encode_labeled_frames(b"dosr");
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].0.len(), 6);
+    assert_eq!(frames[1].0.len(), 2);
+}
+
+/// Misses the tones of the chunks from the given one to the end of every frame
+#[derive(Debug)]
+struct MissChunksFrom(usize);
+
+impl PeakDetector for MissChunksFrom {
+    fn detect(&self, magnitudes: &[f32], bin_width: f32, config: &Dosr) -> Vec<f32> {
+        let band = 16.0 * config.delta_freq();
+        let low = config.base_freq() + self.0 as f32 * band - config.delta_freq() / 2.0;
+        ThresholdDetector
+            .detect(magnitudes, bin_width, config)
+            .into_iter()
+            .filter(|frequency| *frequency < low)
+            .collect()
+    }
+}
+
+#[test]
+fn missed_last_tone_of_an_inner_frame_reads_as_0() {
+    // The last chunk of the first frame is the low half of the third byte, the final frame
+    // of 2 chunks is unaffected
+    let samples = Dosr::default().encode_data(b"dosr");
+    let decoded = Dosr::default()
+        .with_peak_detector(Box::new(MissChunksFrom(5)))
+        .decode(&samples);
+    assert_eq!(decoded.unwrap(), [b'd', b'o', b's' & 0xf0, b'r']);
+}
+
+#[test]
+fn missed_last_tone_of_a_full_final_frame_is_truncation() {
+    // 6 bytes are 12 chunks, two full frames
+    let samples = Dosr::default().encode_data(b"dosr!!");
+    let decoded = Dosr::default()
+        .with_peak_detector(Box::new(MissChunksFrom(5)))
+        .decode(&samples);
+    assert!(
+        matches!(decoded, Err(DecodeError::Truncated { declared: None, .. })),
+        "{decoded:?}"
+    );
+}
+
+#[test]
+fn length_prefix_catches_missed_tones_ending_on_a_byte() {
+    // Missing the last two chunks drops a whole byte, only the length tells
+    let dosr = Dosr::default().with_length_prefix(true);
+    // 2 bytes and the prefix are 12 chunks, two full frames
+    let samples = dosr.encode_data(b"!!");
+    let decoded = Dosr::default()
+        .with_length_prefix(true)
+        .with_peak_detector(Box::new(MissChunksFrom(4)))
+        .decode(&samples);
+    assert!(
+        matches!(
+            decoded,
+            Err(DecodeError::Truncated {
+                declared: Some(2),
+                received: 1
+            })
+        ),
+        "{decoded:?}"
+    );
+}
@@ -0,0 +1,39 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"digital silence";
+
+#[test]
+fn digital_silence_decodes_to_nothing() {
+    let dosr = Dosr::default();
+    let samples = vec![0.0; 4 * dosr.samples_per_frame()];
+
+    assert_eq!(dosr.decode(&samples).unwrap(), Vec::<u8>::new());
+
+    let (decoded, report) = dosr.decode_with_metrics(&samples);
+    assert_eq!(decoded.unwrap(), Vec::<u8>::new());
+    assert!(
+        report
+            .frames
+            .iter()
+            .all(|f| f.values.is_empty() && !f.snr_db.is_nan())
+    );
+}
+
+#[test]
+fn trailing_silent_frames_add_no_bytes() {
+    let dosr = Dosr::default();
+    let mut samples = dosr.encode_data(MESSAGE);
+    samples.extend(vec![0.0; 3 * dosr.samples_per_frame()]);
+
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn raised_threshold_ignores_quiet_signals() {
+    // Six tones of amplitude 2e-3 make an RMS of about 3.5e-3
+    let quiet = Dosr::default().with_amplitude(2e-3).encode_data(MESSAGE);
+
+    assert_eq!(Dosr::default().decode(&quiet).unwrap(), MESSAGE);
+    let dosr = Dosr::default().with_silence_threshold(1e-2);
+    assert_eq!(dosr.decode(&quiet).unwrap(), Vec::<u8>::new());
+}
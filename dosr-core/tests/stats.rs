@@ -0,0 +1,76 @@
+use std::f32::consts::TAU;
+
+use dosr::{Dosr, add_awgn};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+const MESSAGE: &[u8] = b"signal quality";
+
+#[test]
+fn noise_lowers_the_minimum_snr() {
+    let dosr = Dosr::default();
+    let clean = dosr.encode_data(MESSAGE);
+    let mut noisy = clean.clone();
+    add_awgn(&mut noisy, 10.0, &mut XorShiftRng::seed_from_u64(3));
+
+    let (decoded, clean_report) = dosr.decode_with_metrics(&clean);
+    assert_eq!(decoded.unwrap(), MESSAGE);
+    let (decoded, noisy_report) = dosr.decode_with_metrics(&noisy);
+    assert_eq!(decoded.unwrap(), MESSAGE);
+
+    let clean_snr_db = clean_report.min_snr_db().unwrap();
+    let noisy_snr_db = noisy_report.min_snr_db().unwrap();
+    assert!(
+        noisy_snr_db < clean_snr_db,
+        "{noisy_snr_db} dB with noise, {clean_snr_db} dB without"
+    );
+}
+
+#[test]
+fn missing_tones_are_counted() {
+    let dosr = Dosr::default();
+    let mut samples = dosr.encode_data(MESSAGE);
+    let (_, report) = dosr.decode_with_metrics(&samples);
+    let unexpected = report.unexpected_tone_frames();
+
+    // A single tone in place of the six of a frame halfway through the message
+    let spf = dosr.samples_per_frame();
+    let frame = samples.len() / spf / 2;
+    assert!(!report.frames[frame].unexpected_tones);
+    let tone = dosr.base_freq();
+    for (n, s) in samples[frame * spf..(frame + 1) * spf]
+        .iter_mut()
+        .enumerate()
+    {
+        *s = 0.5 * (TAU * tone * n as f32 / dosr.sample_rate()).sin();
+    }
+
+    let (_, report) = dosr.decode_with_metrics(&samples);
+    assert!(report.frames[frame].unexpected_tones);
+    assert_eq!(report.unexpected_tone_frames(), unexpected + 1);
+}
+
+#[test]
+fn silence_has_no_minimum_snr() {
+    let dosr = Dosr::default();
+    let (_, report) = dosr.decode_with_metrics(&vec![0.0; 3 * dosr.samples_per_frame()]);
+
+    assert_eq!(report.unexpected_tone_frames(), 0);
+    assert_eq!(report.min_snr_db(), None);
+}
+
+#[test]
+fn stats_are_the_metrics_report() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_data(MESSAGE);
+    let (decoded, stats) = dosr.decode_with_stats(&samples);
+    let (_, report) = dosr.decode_with_metrics(&samples);
+
+    assert_eq!(decoded.unwrap(), MESSAGE);
+    assert_eq!(stats.frames.len(), report.frames.len());
+    assert_eq!(
+        stats.unexpected_tone_frames(),
+        report.unexpected_tone_frames()
+    );
+    assert_eq!(stats.min_snr_db(), report.min_snr_db());
+}
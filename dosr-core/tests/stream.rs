@@ -0,0 +1,42 @@
+#![cfg(feature = "async")]
+
+use futures::StreamExt;
+
+use dosr::Dosr;
+
+fn to_le_bytes(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+}
+
+#[tokio::test]
+async fn transmissions_separated_by_silence_are_yielded_apart() {
+    let dosr = Dosr::default();
+    let spf = dosr.samples_per_frame();
+    let samples = [
+        dosr.encode_data(b"first"),
+        vec![0.0; spf],
+        dosr.encode_data(b"second"),
+    ]
+    .concat();
+    let bytes = to_le_bytes(&samples);
+
+    let decoded = dosr
+        .decode_async(bytes.as_slice())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(decoded, [Ok(b"first".to_vec()), Ok(b"second".to_vec())]);
+}
+
+#[tokio::test]
+async fn trailing_partial_sample_is_dropped() {
+    let dosr = Dosr::default();
+    let mut bytes = to_le_bytes(&dosr.encode_data(b"ragged end"));
+    bytes.extend([0x12, 0x34, 0x56]);
+    assert_ne!(bytes.len() % 4, 0);
+
+    let decoded = dosr
+        .decode_async(bytes.as_slice())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(decoded, [Ok(b"ragged end".to_vec())]);
+}
@@ -0,0 +1,66 @@
+use std::f32::consts::TAU;
+
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"weak signal";
+
+/// Tones at 30% of the amplitude of a hum off the grid, which sets the normalization of every
+/// frame
+fn weak_signal(dosr: &Dosr) -> Vec<f32> {
+    dosr.encode_data(MESSAGE)
+        .iter()
+        .enumerate()
+        .map(|(n, s)| s + 0.5 * (TAU * 8000.0 * n as f32 / dosr.sample_rate()).sin())
+        .collect()
+}
+
+/// Six tones at 0.15, which add up to less than full scale
+fn config() -> Dosr {
+    Dosr::default().with_amplitude(0.15)
+}
+
+#[test]
+fn default_threshold_misses_weak_tones() {
+    let dosr = config();
+    assert_ne!(dosr.decode(&weak_signal(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn lowered_threshold_detects_weak_tones() {
+    let dosr = config().with_peak_threshold(0.1);
+    assert_eq!(dosr.decode(&weak_signal(&dosr)).unwrap(), MESSAGE);
+}
+
+/// The message through a first-difference channel, which attenuates the lowest band to under
+/// a third of the highest
+fn sloped_signal(dosr: &Dosr) -> Vec<f32> {
+    let samples = dosr.encode_data(MESSAGE);
+    std::iter::once(0.0)
+        .chain(samples.iter().copied())
+        .zip(&samples)
+        .map(|(previous, sample)| sample - 0.95 * previous)
+        .collect()
+}
+
+#[test]
+fn single_threshold_misses_the_attenuated_bands() {
+    let dosr = Dosr::default();
+    assert_ne!(dosr.decode(&sloped_signal(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn band_thresholds_follow_the_slope() {
+    let dosr = Dosr::default().with_band_thresholds(vec![0.15, 0.2, 0.25, 0.3, 0.35, 0.4]);
+    assert_eq!(dosr.decode(&sloped_signal(&dosr)).unwrap(), MESSAGE);
+}
+
+#[test]
+fn band_thresholds_apply_on_a_scale() {
+    let dosr = Dosr::new(220.0, 10.0, 2, 4, 0.1, 48000.0)
+        .with_scale(dosr::Scale::Major)
+        .with_band_thresholds(vec![0.3; 4]);
+    // Twice, the second decode reusing the bands of the bins
+    for _ in 0..2 {
+        assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+    }
+}
@@ -0,0 +1,28 @@
+use dosr::Dosr;
+
+const MESSAGE: &[u8] = b"a little late";
+
+/// The message starting 40% of a frame into the recording, after a quiet hiss
+fn late_start(dosr: &Dosr) -> Vec<f32> {
+    let lead = dosr.samples_per_frame() * 2 / 5;
+    (0..lead)
+        .map(|n| if n % 2 == 0 { 1e-4 } else { -1e-4 })
+        .chain(dosr.encode_data(MESSAGE))
+        .collect()
+}
+
+#[test]
+fn timing_search_finds_a_late_start() {
+    let dosr = Dosr::default();
+    let samples = late_start(&dosr);
+    assert_ne!(dosr.decode(&samples).unwrap_or_default(), MESSAGE);
+
+    let searching = Dosr::default().with_timing_search(10);
+    assert_eq!(searching.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn timing_search_keeps_an_aligned_start() {
+    let dosr = Dosr::default().with_timing_search(10);
+    assert_eq!(dosr.decode(&dosr.encode_data(MESSAGE)).unwrap(), MESSAGE);
+}
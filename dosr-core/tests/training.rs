@@ -0,0 +1,55 @@
+use dosr::{Dosr, PeakDetector, ThresholdDetector};
+
+const MESSAGE: &[u8] = b"through a tilted channel";
+
+/// Pre-emphasis style first difference, attenuating the low tones of the grid to under a third
+/// of the high ones
+fn sloped(samples: &[f32]) -> Vec<f32> {
+    const ALPHA: f32 = 0.95;
+    std::iter::once(0.0)
+        .chain(samples.iter().copied())
+        .zip(samples)
+        .map(|(previous, sample)| sample - ALPHA * previous)
+        .collect()
+}
+
+#[test]
+fn training_flattens_a_sloped_channel() {
+    let dosr = Dosr::default();
+    let samples = sloped(&dosr.encode_with_training(MESSAGE));
+    assert_eq!(dosr.decode_with_training(&samples).unwrap(), MESSAGE);
+
+    let plain = sloped(&dosr.encode_data(MESSAGE));
+    assert_ne!(dosr.decode(&plain).unwrap(), MESSAGE);
+}
+
+#[test]
+fn training_round_trips_on_a_flat_channel() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_with_training(MESSAGE);
+    assert_eq!(dosr.decode_with_training(&samples).unwrap(), MESSAGE);
+}
+
+/// Fails on the magnitudes of a spectrum that couldn't be normalized
+#[derive(Debug)]
+struct FiniteMagnitudes;
+
+impl PeakDetector for FiniteMagnitudes {
+    fn detect(&self, magnitudes: &[f32], bin_width: f32, config: &Dosr) -> Vec<f32> {
+        assert!(magnitudes.iter().all(|m| m.is_finite()), "{magnitudes:?}");
+        ThresholdDetector.detect(magnitudes, bin_width, config)
+    }
+}
+
+#[test]
+fn digital_silence_decodes_to_an_empty_frame() {
+    let dosr = Dosr::default();
+    let mut samples = dosr.encode_with_training(MESSAGE);
+    // A frame of digital silence a few frames into the payload
+    let spf = dosr.samples_per_frame();
+    samples[6 * spf..7 * spf].fill(0.0);
+    let decoded = dosr
+        .with_peak_detector(Box::new(FiniteMagnitudes))
+        .decode_with_training(&samples);
+    assert_ne!(decoded.unwrap_or_default(), MESSAGE);
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "wav")]
+
+use std::{
+    io::{Cursor, ErrorKind},
+    path::Path,
+};
+
+use dosr::{DecodeError, Dosr, WavFormat, read_wav};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+
+const MESSAGE: &[u8] = b"through 16-bit PCM";
+
+#[test]
+fn int16_round_trip() {
+    let dosr = Dosr::default().with_amplitude(1.0 / 6.0);
+    let mut wav = Cursor::new(vec![]);
+    dosr.encode(MESSAGE)
+        .write_wav_as(&mut wav, WavFormat::Int16)
+        .unwrap();
+    wav.set_position(0);
+
+    let reader = WavReader::new(wav).unwrap();
+    assert_eq!(reader.spec().bits_per_sample, 16);
+    assert_eq!(reader.spec().sample_format, SampleFormat::Int);
+    let samples = reader
+        .into_samples::<i16>()
+        .map(|s| s.unwrap() as f32 / i16::MAX as f32)
+        .collect::<Vec<_>>();
+    assert_eq!(dosr.decode(&samples).unwrap(), MESSAGE);
+}
+
+#[test]
+fn file_round_trip() {
+    let dosr = Dosr::default();
+    let path = std::env::temp_dir().join(format!("dosr-round-trip-{}.wav", std::process::id()));
+    dosr.encode_to_wav(MESSAGE, &path).unwrap();
+    let decoded = dosr.decode_from_wav(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(decoded.unwrap(), MESSAGE);
+}
+
+#[test]
+fn file_at_another_rate_is_an_error() {
+    let path = std::env::temp_dir().join(format!("dosr-rate-{}.wav", std::process::id()));
+    Dosr::default()
+        .with_sample_rate(44100.0)
+        .encode_to_wav(MESSAGE, &path)
+        .unwrap();
+    let decoded = Dosr::default().decode_from_wav(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        decoded,
+        Err(DecodeError::WavSampleRate {
+            sample_rate: 44100,
+            expected: 48000
+        })
+    );
+}
+
+#[test]
+fn missing_file_is_an_error() {
+    let decoded = Dosr::default().decode_from_wav(Path::new("does/not/exist.wav"));
+    let Err(DecodeError::Wav(err)) = decoded else {
+        panic!("Decoded a missing file: {decoded:?}");
+    };
+    assert!(matches!(&*err.0, hound::Error::IoError(err) if err.kind() == ErrorKind::NotFound));
+}
+
+#[test]
+fn malformed_file_keeps_the_reader_error() {
+    let path = std::env::temp_dir().join(format!("dosr-malformed-{}.wav", std::process::id()));
+    std::fs::write(&path, b"not a WAV file").unwrap();
+    let decoded = Dosr::default().decode_from_wav(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let Err(DecodeError::Wav(err)) = decoded else {
+        panic!("Decoded a malformed file: {decoded:?}");
+    };
+    assert!(matches!(&*err.0, hound::Error::FormatError(_)), "{err}");
+}
+
+/// Writes the samples with hound as a mono WAV of the given format
+fn hound_wav(samples: &[f32], bits_per_sample: u16, sample_format: SampleFormat) -> Vec<u8> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample,
+        sample_format,
+    };
+    let mut wav = Cursor::new(vec![]);
+    let mut writer = WavWriter::new(&mut wav, spec).unwrap();
+    let scale = (1i64 << (bits_per_sample - 1)) as f32 - 1.0;
+    for s in samples {
+        match sample_format {
+            SampleFormat::Float => writer.write_sample(*s).unwrap(),
+            SampleFormat::Int => writer.write_sample((s * scale).round() as i32).unwrap(),
+        }
+    }
+    writer.finalize().unwrap();
+    wav.into_inner()
+}
+
+#[test]
+fn read_wav_decodes_int_and_float_files() {
+    let dosr = Dosr::default();
+    let samples = dosr.encode_data(MESSAGE);
+    for (bits, format) in [
+        (16, SampleFormat::Int),
+        (24, SampleFormat::Int),
+        (32, SampleFormat::Float),
+    ] {
+        let wav = hound_wav(&samples, bits, format);
+        let (read, spec) = read_wav(wav.as_slice()).unwrap();
+        assert_eq!((spec.bits_per_sample, spec.sample_format), (bits, format));
+        assert_eq!(read.len(), samples.len());
+        assert_eq!(
+            dosr.decode(&read).unwrap(),
+            MESSAGE,
+            "{bits}-bit {format:?}"
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use dosr::{Dosr, add_awgn};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+/// Deterministic white noise in [-amplitude, amplitude)
+fn noise(len: usize, amplitude: f32) -> Vec<f32> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            amplitude * ((state >> 40) as f32 / (1 << 23) as f32 - 1.0)
+        })
+        .collect()
+}
+
+/// Ratio of the standard deviation to the mean of the noise magnitudes over the tone grid
+fn noise_floor_spread(dosr: &Dosr, samples: &[f32]) -> f32 {
+    let magnitudes = dosr.band_spectrogram(samples).concat();
+    let mean = magnitudes.iter().sum::<f32>() / magnitudes.len() as f32;
+    let variance =
+        magnitudes.iter().map(|m| (m - mean).powi(2)).sum::<f32>() / magnitudes.len() as f32;
+    variance.sqrt() / mean
+}
+
+#[test]
+fn averaging_steadies_the_noise_floor() {
+    let dosr = Dosr::default();
+    let samples = noise(20 * dosr.samples_per_frame(), 0.5);
+    let single = noise_floor_spread(&dosr, &samples);
+    let averaged = noise_floor_spread(&Dosr::default().with_welch_segments(4), &samples);
+    assert!(
+        averaged < 0.75 * single,
+        "averaged spread {averaged} not below single {single}"
+    );
+}
+
+#[test]
+fn averaged_decode_round_trips() {
+    let message = b"the quick brown fox";
+    let dosr = Dosr::default().with_welch_segments(3);
+    let mut samples = dosr.encode_data(message);
+    add_awgn(&mut samples, 0.0, &mut XorShiftRng::seed_from_u64(7));
+    assert_eq!(dosr.decode(&samples).unwrap(), message);
+}